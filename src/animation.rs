@@ -0,0 +1,118 @@
+/// A sequence of frames, each shown for some number of ticks.
+#[derive(Clone)]
+pub struct AnimationClip<F> {
+  frames: Vec<(F, usize)>,
+  looping: bool,
+}
+
+impl<F: Clone> AnimationClip<F> {
+  /// A clip that plays through once, then stays on its last frame.
+  pub fn once(frames: Vec<(F, usize)>) -> Self {
+    Self {
+      frames,
+      looping: false,
+    }
+  }
+
+  /// A clip that starts over each time it reaches the end.
+  pub fn looping(frames: Vec<(F, usize)>) -> Self {
+    let clip = Self {
+      frames,
+      looping: true,
+    };
+    assert!(clip.duration() > 0, "A looping clip must take some time");
+    clip
+  }
+
+  fn duration(&self) -> usize {
+    self.frames.iter().map(|&(_, ticks)| ticks).sum()
+  }
+
+  /// The frame shown `dt` ticks into the clip, or the last frame once it is
+  /// over. Frames that last no ticks are only shown if they come last.
+  fn frame_at(&self, mut dt: usize) -> &F {
+    for (frame, ticks) in &self.frames {
+      if dt < *ticks {
+        return frame;
+      }
+      dt -= ticks;
+    }
+    &self.frames.last().expect("A clip must have frames").0
+  }
+}
+
+struct Playing<F, E> {
+  clip: AnimationClip<F>,
+  start: usize,
+  on_done: Option<E>,
+  /// How many times a looping clip has reached its end.
+  loops: usize,
+}
+
+/// Plays clips one at a time, keeping track of which frame to show. When a
+/// clip reaches its end, its event is returned from `tick`: once for clips
+/// that play once, and on every loop for looping clips.
+pub struct Animator<F, E> {
+  t: usize,
+  frame: F,
+  playing: Option<Playing<F, E>>,
+}
+
+impl<F: Clone, E: Clone> Animator<F, E> {
+  pub fn new(frame: F) -> Self {
+    Self {
+      t: 0,
+      frame,
+      playing: None,
+    }
+  }
+
+  pub fn frame(&self) -> &F {
+    &self.frame
+  }
+
+  /// Stops the playing clip, if any, and shows `frame` until told otherwise.
+  pub fn show(&mut self, frame: F) {
+    self.playing = None;
+    self.frame = frame;
+  }
+
+  /// Plays `clip` from the start, in place of the playing clip.
+  pub fn play(&mut self, clip: AnimationClip<F>, on_done: Option<E>) {
+    self.frame = clip.frame_at(0).clone();
+    self.playing = Some(Playing {
+      clip,
+      start: self.t,
+      on_done,
+      loops: 0,
+    });
+  }
+
+  /// Moves on to time `t`, returning the events of the clips that reached
+  /// their end since the last tick.
+  pub fn tick(&mut self, t: usize) -> Vec<E> {
+    self.t = t;
+    let Some(playing) = &mut self.playing else {
+      return Vec::new();
+    };
+
+    let dt = t - playing.start;
+    let duration = playing.clip.duration();
+    if playing.clip.looping {
+      let loops = dt / duration;
+      let events = (playing.loops..loops)
+        .filter_map(|_| playing.on_done.clone())
+        .collect();
+      playing.loops = loops;
+      self.frame = playing.clip.frame_at(dt % duration).clone();
+      events
+    } else if dt < duration {
+      self.frame = playing.clip.frame_at(dt).clone();
+      Vec::new()
+    } else {
+      let playing = self.playing.take().unwrap();
+      self.frame = playing.clip.frame_at(dt).clone();
+      playing.on_done.into_iter().collect()
+    }
+  }
+}
@@ -0,0 +1,254 @@
+use std::{path::PathBuf, str::FromStr};
+
+pub const USAGE: &str = "\
+Usage: term-anim [OPTIONS]
+
+Options:
+  --seed <N>               Seed for the random number generator
+  --seed-from-name <NAME>  Derive the seed from the first 8 bytes of NAME
+  --width <COLUMNS>        Width of the window, instead of the terminal's
+  --height <ROWS>          Height of the window, instead of the terminal's
+  --fps <N>                Frames per second [default: 50]
+  --message <TEXT>         The greeting the bunny finds
+  --message-file <FILE>    Read the greeting from FILE
+  --story <FILE>           Play the story script in FILE instead of the default
+  --assets <DIR>           Load sprites from DIR, in place of the built-in ones
+  --tuning <FILE>          Read positions and speeds from FILE, reloading it
+                           whenever it changes
+  --profile <FILE>         Write a flamegraph of the run to FILE, and print how
+                           many bytes were drawn
+  --record <FILE>          Write an asciicast recording of the run to FILE
+  --record-input <FILE>    Write the inputs of the run to FILE, for --replay
+  --replay <FILE>          Play back inputs recorded with --record-input,
+                           including tuning reloads
+  --headless               Run without a terminal, as fast as possible
+  --frames <N>             Stop after N frames
+  --summary                Print the final frame as plain text after quitting,
+                           and how many bytes were drawn
+  -h, --help               Print this help
+";
+
+/// The largest `--width` and `--height`, well past any terminal, which keeps
+/// the window's buffers from growing past what can be allocated.
+pub const MAX_SIZE: u32 = 10000;
+
+/// Options parsed from the command line.
+#[derive(Default)]
+pub struct Args {
+  /// Seed for the random number generator.
+  pub seed: Option<u64>,
+  /// Size of the window, overriding the terminal size.
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  /// Frames per second.
+  pub fps: Option<u32>,
+  /// The greeting revealed over the course of the story.
+  pub message: Option<String>,
+  /// A file to read the greeting from.
+  pub message_file: Option<PathBuf>,
+  /// A story script to play instead of the default one.
+  pub story: Option<PathBuf>,
+  /// A directory of sprites to use instead of the built-in ones.
+  pub assets: Option<PathBuf>,
+  /// A tuning file, read again whenever it changes.
+  pub tuning: Option<PathBuf>,
+  /// Where to write a flamegraph of the run. How many bytes were drawn is
+  /// printed too.
+  pub profile: Option<PathBuf>,
+  /// Where to write an asciicast recording of the run.
+  pub record: Option<PathBuf>,
+  /// Where to write the inputs of the run, for later replay.
+  pub record_input: Option<PathBuf>,
+  /// A recording of inputs to play back instead of reading the mouse and
+  /// keyboard.
+  pub replay: Option<PathBuf>,
+  /// Run without a terminal, as fast as possible.
+  pub headless: bool,
+  /// Stop after this many frames.
+  pub frames: Option<usize>,
+  /// Print the final frame as plain text after quitting, and how many bytes
+  /// were drawn.
+  pub summary: bool,
+  /// Print usage and exit.
+  pub help: bool,
+}
+
+impl Args {
+  /// The flags and values given that change what is played, and so have to
+  /// match between recording a run and replaying it.
+  pub fn content_options(&self) -> Vec<(&'static str, String)> {
+    let path = |path: &Option<PathBuf>| path.as_ref().map(|path| path.display().to_string());
+    // The message file is read into `message`, so it takes precedence.
+    let message = match &self.message_file {
+      Some(_) => None,
+      None => self.message.clone(),
+    };
+    [
+      ("--message", message),
+      ("--message-file", path(&self.message_file)),
+      ("--story", path(&self.story)),
+      ("--assets", path(&self.assets)),
+    ]
+    .into_iter()
+    .filter_map(|(flag, value)| Some((flag, value?)))
+    .collect()
+  }
+
+  pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--seed" => parsed.seed = Some(number(&arg, &mut args, "a 64-bit number")?),
+        "--seed-from-name" => parsed.seed = Some(seed_from_name(&value(&arg, &mut args)?)),
+        "--width" => parsed.width = Some(number(&arg, &mut args, "a column count")?),
+        "--height" => parsed.height = Some(number(&arg, &mut args, "a row count")?),
+        "--fps" => parsed.fps = Some(number(&arg, &mut args, "a frame rate")?),
+        "--message" => parsed.message = Some(value(&arg, &mut args)?),
+        "--message-file" => parsed.message_file = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--story" => parsed.story = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--assets" => parsed.assets = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--tuning" => parsed.tuning = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--profile" => parsed.profile = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--record" => parsed.record = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--record-input" => parsed.record_input = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--replay" => parsed.replay = Some(PathBuf::from(value(&arg, &mut args)?)),
+        "--headless" => parsed.headless = true,
+        "--frames" => parsed.frames = Some(number(&arg, &mut args, "a frame count")?),
+        "--summary" => parsed.summary = true,
+        "-h" | "--help" => parsed.help = true,
+        _ => return Err(format!("Unknown argument '{arg}'")),
+      }
+    }
+
+    let size_range = 1..=MAX_SIZE;
+    if [parsed.width, parsed.height]
+      .iter()
+      .flatten()
+      .any(|size| !size_range.contains(size))
+    {
+      return Err(format!(
+        "--width and --height must be between 1 and {MAX_SIZE}"
+      ));
+    }
+    if parsed.fps.is_some_and(|fps| !(1..=1000).contains(&fps)) {
+      return Err("--fps must be between 1 and 1000".to_string());
+    }
+    if parsed.message.is_some() && parsed.message_file.is_some() {
+      return Err("--message and --message-file can't be used together".to_string());
+    }
+    if parsed.headless && parsed.frames.is_none() && parsed.replay.is_none() {
+      return Err("--headless requires --frames or --replay".to_string());
+    }
+    if parsed.replay.is_some() && parsed.record_input.is_some() {
+      return Err("--replay and --record-input can't be used together".to_string());
+    }
+    if parsed.replay.is_some()
+      && (parsed.seed.is_some() || parsed.width.is_some() || parsed.height.is_some())
+    {
+      return Err("--replay sets the seed and size, so they can't be given too".to_string());
+    }
+    if parsed.replay.is_some() && parsed.tuning.is_some() {
+      return Err(
+        "--replay plays back the recorded tuning, so --tuning can't be given".to_string(),
+      );
+    }
+    Ok(parsed)
+  }
+}
+
+/// The seed for `name`: its first 8 bytes as a little-endian number, padded
+/// with zeros if it is shorter.
+fn seed_from_name(name: &str) -> u64 {
+  let mut bytes = [0; 8];
+  let len = name.len().min(8);
+  bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+  u64::from_le_bytes(bytes)
+}
+
+fn value<I: Iterator<Item = String>>(arg: &str, args: &mut I) -> Result<String, String> {
+  args
+    .next()
+    .ok_or_else(|| format!("Missing value for {arg}"))
+}
+
+fn number<N: FromStr, I: Iterator<Item = String>>(
+  arg: &str,
+  args: &mut I,
+  expected: &str,
+) -> Result<N, String> {
+  let value = value(arg, args)?;
+  value
+    .parse()
+    .map_err(|_| format!("Invalid value '{value}' for {arg}, expected {expected}"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &str) -> Result<Args, String> {
+    Args::parse(args.split_whitespace().map(String::from))
+  }
+
+  #[test]
+  fn parses_options() {
+    let args = parse("--seed 7 --width 80 --height 24 --headless --frames 10 --summary").unwrap();
+    assert_eq!(args.seed, Some(7));
+    assert_eq!((args.width, args.height), (Some(80), Some(24)));
+    assert!(args.headless && args.summary);
+    assert_eq!(args.frames, Some(10));
+    let args = parse("--width 10000 --height 1").unwrap();
+    assert_eq!((args.width, args.height), (Some(10000), Some(1)));
+  }
+
+  #[test]
+  fn rejects_bad_arguments() {
+    let err = |args: &str| parse(args).err().unwrap();
+    assert_eq!(err("--bogus"), "Unknown argument '--bogus'");
+    assert_eq!(err("--seed"), "Missing value for --seed");
+    assert_eq!(
+      err("--fps fast"),
+      "Invalid value 'fast' for --fps, expected a frame rate"
+    );
+    let size = "--width and --height must be between 1 and 10000";
+    assert_eq!(err("--width 0"), size);
+    assert_eq!(err("--height 0"), size);
+    assert_eq!(err("--width 100000 --height 100000"), size);
+    assert_eq!(err("--height 10001"), size);
+    assert_eq!(err("--fps 0"), "--fps must be between 1 and 1000");
+    assert_eq!(err("--fps 1001"), "--fps must be between 1 and 1000");
+    assert_eq!(
+      err("--message hi --message-file hi.txt"),
+      "--message and --message-file can't be used together"
+    );
+    assert_eq!(
+      err("--headless"),
+      "--headless requires --frames or --replay"
+    );
+    assert_eq!(
+      err("--replay a --record-input b"),
+      "--replay and --record-input can't be used together"
+    );
+    for flag in ["--seed 1", "--width 80", "--height 24"] {
+      assert_eq!(
+        err(&format!("--replay a {flag}")),
+        "--replay sets the seed and size, so they can't be given too"
+      );
+    }
+    assert_eq!(
+      err("--replay a --tuning b"),
+      "--replay plays back the recorded tuning, so --tuning can't be given"
+    );
+  }
+
+  #[test]
+  fn seeds_from_the_first_eight_bytes_of_a_name() {
+    assert_eq!(seed_from_name(""), 0);
+    assert_eq!(seed_from_name("a"), 0x61);
+    assert_eq!(seed_from_name("ab"), 0x6261);
+    assert_eq!(seed_from_name("abcdefgh"), u64::from_le_bytes(*b"abcdefgh"));
+    assert_eq!(seed_from_name("abcdefghij"), seed_from_name("abcdefgh"));
+    assert_eq!(parse("--seed-from-name ab").unwrap().seed, Some(0x6261));
+  }
+}
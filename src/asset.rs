@@ -0,0 +1,415 @@
+//! Sprite files, so the art can be changed without recompiling.
+//!
+//! A sprite file is split into sections, each starting with a `[name]` line:
+//!
+//! - `[art]`: the characters of the sprite, one row per line. Spaces are
+//!   see-through.
+//! - `[colors]`: which color from the palette each character of the art is
+//!   drawn in. Cells that are blank here use the default color.
+//! - `[mask]`: which cells are drawn, spaces in the art included. Only cells
+//!   that aren't blank here are drawn.
+//! - `[palette]`: a `<key> #rrggbb` line per color, where `key` is a
+//!   character of the color layer, or `default`.
+//!
+//! Only `[art]` is required. A variant of the sprite is made with
+//! `[mask <name>]`, which replaces the mask, and `[palette <name>]`, which
+//! adds to the palette.
+//!
+//! Layers are read verbatim, apart from blank lines at their end, so a row
+//! can't be in square brackets. Elsewhere, blank lines and lines starting
+//! with `#` are ignored.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{color::Rgb, sprite::Sprite};
+
+/// Every sprite, with the variants of it that are drawn.
+const SPRITES: [(&str, &str, &[&str]); 14] = [
+  (
+    "bunny_sleep",
+    include_str!("../assets/bunny_sleep.sprite"),
+    &[],
+  ),
+  (
+    "bunny_wake",
+    include_str!("../assets/bunny_wake.sprite"),
+    &[],
+  ),
+  (
+    "bunny_step1",
+    include_str!("../assets/bunny_step1.sprite"),
+    &[],
+  ),
+  (
+    "bunny_step2",
+    include_str!("../assets/bunny_step2.sprite"),
+    &[],
+  ),
+  (
+    "bunny_blink",
+    include_str!("../assets/bunny_blink.sprite"),
+    &[],
+  ),
+  (
+    "bunny_hold_kazoo",
+    include_str!("../assets/bunny_hold_kazoo.sprite"),
+    &[],
+  ),
+  (
+    "bunny_blow_kazoo",
+    include_str!("../assets/bunny_blow_kazoo.sprite"),
+    &[],
+  ),
+  (
+    "bunny_munch",
+    include_str!("../assets/bunny_munch.sprite"),
+    &[],
+  ),
+  (
+    "train_engine",
+    include_str!("../assets/train_engine.sprite"),
+    &[],
+  ),
+  (
+    "train_cabin",
+    include_str!("../assets/train_cabin.sprite"),
+    &[],
+  ),
+  (
+    "train_caboose",
+    include_str!("../assets/train_caboose.sprite"),
+    &[],
+  ),
+  (
+    "basket",
+    include_str!("../assets/basket.sprite"),
+    &["inside"],
+  ),
+  ("hole", include_str!("../assets/hole.sprite"), &[]),
+  ("kazoo", include_str!("../assets/kazoo.sprite"), &["dim"]),
+];
+
+enum Section {
+  Art,
+  Colors,
+  Mask(String),
+  Palette(String),
+}
+
+#[derive(Default)]
+struct Palette {
+  default: Option<Rgb>,
+  colors: Vec<(char, Rgb)>,
+}
+
+/// A parsed sprite file. Masks and palettes are keyed by variant, with the
+/// unnamed ones under "".
+pub struct SpriteAsset {
+  art: Vec<String>,
+  colors: Option<Vec<String>>,
+  masks: HashMap<String, Vec<String>>,
+  palettes: HashMap<String, Palette>,
+}
+
+impl SpriteAsset {
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let mut art = None;
+    let mut colors = None;
+    let mut masks = HashMap::new();
+    let mut palettes = HashMap::new();
+    let mut section = None;
+
+    for (line_num, line) in text.lines().enumerate().map(|(idx, line)| (idx + 1, line)) {
+      let err = |msg: &str| format!("{line_num}: {msg}: '{line}'");
+      if let Some(name) = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+      {
+        let (kind, variant) = name.trim().split_once(' ').unwrap_or((name.trim(), ""));
+        let variant = variant.trim().to_string();
+        let duplicate = match (kind, variant.as_str()) {
+          ("art", "") => art.replace((line_num, Vec::new())).is_some(),
+          ("colors", "") => colors.replace((line_num, Vec::new())).is_some(),
+          ("mask", _) => masks
+            .insert(variant.clone(), (line_num, Vec::new()))
+            .is_some(),
+          ("palette", _) => palettes
+            .insert(variant.clone(), Palette::default())
+            .is_some(),
+          _ => return Err(err("Unknown section")),
+        };
+        if duplicate {
+          return Err(err("Duplicate section"));
+        }
+        section = Some(match kind {
+          "art" => Section::Art,
+          "colors" => Section::Colors,
+          "mask" => Section::Mask(variant),
+          _ => Section::Palette(variant),
+        });
+        continue;
+      }
+
+      let layer = match &section {
+        Some(Section::Art) => art.as_mut(),
+        Some(Section::Colors) => colors.as_mut(),
+        Some(Section::Mask(variant)) => masks.get_mut(variant),
+        Some(Section::Palette(variant)) => {
+          let line = line.trim();
+          if line.is_empty() || line.starts_with('#') {
+            continue;
+          }
+          let palette: &mut Palette = palettes.get_mut(variant).unwrap();
+          let (key, color) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [key, color] => (*key, color.parse::<Rgb>().map_err(|msg| err(&msg))?),
+            _ => return Err(err("Expected <key> #rrggbb")),
+          };
+          let mut chars = key.chars();
+          match (key, chars.next(), chars.next()) {
+            ("default", _, _) => palette.default = Some(color),
+            (_, Some(c), None) => palette.colors.push((c, color)),
+            _ => return Err(err("The key must be one character, or 'default'")),
+          }
+          continue;
+        }
+        None => {
+          if !line.trim().is_empty() && !line.starts_with('#') {
+            return Err(err("Expected a section, like [art]"));
+          }
+          continue;
+        }
+      };
+      layer.unwrap().1.push(line.to_string());
+    }
+
+    // Without an [art] section, the whole file is read before that's known.
+    let end = text.lines().count().max(1);
+    let (_, art) = art.ok_or_else(|| format!("{end}: Missing the [art] section"))?;
+    let art = trim_layer(art);
+    let width = art.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let fit = |(line_num, rows): (usize, Vec<String>)| {
+      let rows = trim_layer(rows);
+      if rows.len() > art.len() || rows.iter().any(|row| row.chars().count() > width) {
+        return Err(format!("{line_num}: The layer is bigger than the art"));
+      }
+      Ok((line_num, rows))
+    };
+    let colors = colors.map(fit).transpose()?;
+    let masks = masks
+      .into_iter()
+      .map(|(variant, layer)| Ok((variant, fit(layer)?.1)))
+      .collect::<Result<_, String>>()?;
+
+    let palette = palettes.entry(String::new()).or_default();
+    if let Some((line_num, rows)) = &colors {
+      for (row_idx, row) in rows.iter().enumerate() {
+        let missing = row
+          .chars()
+          .find(|&c| c != ' ' && !palette.colors.iter().any(|&(key, _)| key == c));
+        if let Some(key) = missing {
+          return Err(format!(
+            "{}: '{key}' is not in the palette: '{row}'",
+            line_num + 1 + row_idx
+          ));
+        }
+      }
+    }
+
+    Ok(Self {
+      art,
+      colors: colors.map(|(_, rows)| rows),
+      masks,
+      palettes,
+    })
+  }
+
+  fn has_variant(&self, variant: &str) -> bool {
+    self.masks.contains_key(variant) || self.palettes.contains_key(variant)
+  }
+
+  /// The sprite drawn with its unnamed mask and palette.
+  pub fn sprite(&self) -> Sprite {
+    self.variant("")
+  }
+
+  /// The sprite drawn as `variant`.
+  pub fn variant(&self, variant: &str) -> Sprite {
+    let mut sprite = Sprite::new(&rows(&self.art));
+    if let Some(mask) = self.masks.get(variant).or(self.masks.get("")) {
+      sprite = sprite.with_mask(&rows(mask));
+    }
+
+    let base = &self.palettes[""];
+    let palette = self.palettes.get(variant);
+    if let Some(default) = palette.and_then(|palette| palette.default).or(base.default) {
+      sprite = sprite.with_fg(default);
+    }
+    if let Some(colors) = &self.colors {
+      // Colors of the variant come first, so they are found first.
+      let palette: Vec<_> = palette
+        .iter()
+        .flat_map(|palette| palette.colors.iter())
+        .chain(base.colors.iter())
+        .copied()
+        .collect();
+      sprite = sprite.with_colors(&rows(colors), &palette);
+    }
+    sprite
+  }
+}
+
+/// The sprites for everything in the scene.
+pub struct Assets {
+  sprites: HashMap<&'static str, SpriteAsset>,
+}
+
+impl Assets {
+  /// The sprites built into the program.
+  pub fn builtin() -> Self {
+    let sprites = SPRITES
+      .iter()
+      .map(|&(name, text, variants)| {
+        let asset =
+          SpriteAsset::parse(text).unwrap_or_else(|err| panic!("Built-in sprite {name}:{err}"));
+        check_variants(&asset, variants)
+          .unwrap_or_else(|err| panic!("Built-in sprite {name}: {err}"));
+        (name, asset)
+      })
+      .collect();
+    Self { sprites }
+  }
+
+  /// The built-in sprites, with any of them found in `dir` replaced by the
+  /// files there.
+  pub fn load(dir: &Path) -> Result<Self, String> {
+    let mut assets = Self::builtin();
+    let entries =
+      fs::read_dir(dir).map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+    let mut paths = entries
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+    paths.sort();
+
+    for path in paths {
+      if path.extension().is_none_or(|ext| ext != "sprite") {
+        continue;
+      }
+      let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+      let &(name, _, variants) = SPRITES
+        .iter()
+        .find(|(name, _, _)| *name == stem)
+        .ok_or_else(|| format!("{}: Unknown sprite '{stem}'", path.display()))?;
+      let text = fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+      let asset = SpriteAsset::parse(&text).map_err(|err| format!("{}:{err}", path.display()))?;
+      check_variants(&asset, variants).map_err(|err| format!("{}: {err}", path.display()))?;
+      assets.sprites.insert(name, asset);
+    }
+    Ok(assets)
+  }
+
+  pub fn get(&self, name: &str) -> &SpriteAsset {
+    self
+      .sprites
+      .get(name)
+      .unwrap_or_else(|| panic!("No sprite named {name}"))
+  }
+}
+
+/// Checks that `asset` has every variant of it that is drawn.
+fn check_variants(asset: &SpriteAsset, variants: &[&str]) -> Result<(), String> {
+  match variants.iter().find(|variant| !asset.has_variant(variant)) {
+    Some(variant) => Err(format!("Missing the '{variant}' variant")),
+    None => Ok(()),
+  }
+}
+
+fn rows(layer: &[String]) -> Vec<&str> {
+  layer.iter().map(String::as_str).collect()
+}
+
+/// Drops the blank lines at the end of a layer.
+fn trim_layer(mut rows: Vec<String>) -> Vec<String> {
+  while rows.last().is_some_and(|row| row.trim().is_empty()) {
+    rows.pop();
+  }
+  rows
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse_err(text: &str) -> String {
+    SpriteAsset::parse(text).err().unwrap()
+  }
+
+  #[test]
+  fn unknown_section() {
+    assert_eq!(
+      parse_err("[art]\n(o.o)\n[shading]\n"),
+      "3: Unknown section: '[shading]'"
+    );
+  }
+
+  #[test]
+  fn missing_art() {
+    assert_eq!(parse_err("[mask]\n#####\n"), "2: Missing the [art] section");
+    assert_eq!(parse_err(""), "1: Missing the [art] section");
+  }
+
+  #[test]
+  fn layer_bigger_than_art() {
+    assert_eq!(
+      parse_err("[art]\n(o.o)\n[mask]\n######\n"),
+      "3: The layer is bigger than the art"
+    );
+    assert_eq!(
+      parse_err("[art]\n(o.o)\n[colors]\nwwwww\nwwwww\n[palette]\nw #ffffff\n"),
+      "3: The layer is bigger than the art"
+    );
+  }
+
+  #[test]
+  fn key_missing_from_palette() {
+    let text = "[art]\n(o.o)\n(>_<)\n[colors]\nwwwww\nwwpww\n[palette]\nw #ffffff\n";
+    assert_eq!(parse_err(text), "6: 'p' is not in the palette: 'wwpww'");
+  }
+
+  #[test]
+  fn mirrored_sprite() {
+    let text = "[art]\n(/<.\n[mask]\n## #\n[colors]\nr  b\n[palette]\nr #ff0000\nb #0000ff\n";
+    let sprite = SpriteAsset::parse(text).unwrap().sprite().mirrored();
+    let tiles: Vec<_> = sprite
+      .draw((0, 0))
+      .map(|(draw, pos)| (draw.item(), draw.fg(), pos))
+      .collect();
+    assert_eq!(
+      tiles,
+      [
+        ('.', Some(Rgb(0, 0, 255)), (0, 0)),
+        ('\\', None, (2, 0)),
+        (')', Some(Rgb(255, 0, 0)), (3, 0)),
+      ]
+    );
+    let art: String = SpriteAsset::parse("[art]\n()/\\<>x\n")
+      .unwrap()
+      .sprite()
+      .mirrored()
+      .draw((0, 0))
+      .map(|(draw, _)| draw.item())
+      .collect();
+    assert_eq!(art, "x<>/\\()");
+  }
+
+  #[test]
+  fn missing_variant() {
+    let asset = SpriteAsset::parse("[art]\n(o.o)\n[mask dim]\n #  \n").unwrap();
+    assert_eq!(check_variants(&asset, &["dim"]), Ok(()));
+    assert_eq!(
+      check_variants(&asset, &["dim", "inside"]),
+      Err("Missing the 'inside' variant".to_string())
+    );
+  }
+}
@@ -1,35 +1,42 @@
-use termion::color;
-
-use crate::{entity::Entity, peach::Peach, util::Draw, water::Water};
-
+use termion::{color, event::Key};
+
+use crate::{
+  asset::Assets,
+  entity::{dispatch, Bounds, Entity},
+  force::ForceFields,
+  greeting::Relayout,
+  peach::Peach,
+  physics::Solids,
+  sprite::Sprite,
+  tuning::Tuning,
+  util::Draw,
+  water::Water,
+};
+
+/// The width and height of the basket.
+pub const SIZE: (i32, i32) = (12, 6);
 const Z_IDX: i32 = 25;
 const BG_Z_IDX: i32 = 20;
 
-#[rustfmt::skip]
-const BASKET: [&str; 6] = [
-  r#"   ======   "#,
-  r#" //      \\ "#,
-  r#"||        ||"#,
-  r#"============"#,
-  r#" \\##||##// "#,
-  r#"   -~~~~-   "#,
-];
-
 pub struct Basket {
   pos: (i32, i32),
   peaches: Vec<Peach>,
+  sprite: Sprite,
+  inside: Sprite,
 }
 
 impl Basket {
-  pub fn new(pos: (i32, i32)) -> Self {
+  pub fn new(pos: (i32, i32), assets: &Assets, forces: &ForceFields) -> Self {
     Self {
       pos,
       peaches: vec![
-        Peach::new(pos.0 + 2, pos.1 + 1, color::AnsiValue::rgb(5, 0, 0)),
-        Peach::new(pos.0 + 5, pos.1 + 1, color::AnsiValue::rgb(5, 0, 1)),
-        Peach::new(pos.0 + 8, pos.1 + 1, color::AnsiValue::rgb(4, 0, 0)),
-        Peach::new(pos.0 + 4, pos.1, color::AnsiValue::rgb(5, 1, 0)),
+        Peach::new(pos.0 + 2, pos.1 + 1, color::AnsiValue::rgb(5, 0, 0), forces),
+        Peach::new(pos.0 + 5, pos.1 + 1, color::AnsiValue::rgb(5, 0, 1), forces),
+        Peach::new(pos.0 + 8, pos.1 + 1, color::AnsiValue::rgb(4, 0, 0), forces),
+        Peach::new(pos.0 + 4, pos.1, color::AnsiValue::rgb(5, 1, 0), forces),
       ],
+      sprite: assets.get("basket").sprite().with_z(Z_IDX),
+      inside: assets.get("basket").variant("inside").with_z(BG_Z_IDX),
     }
   }
 
@@ -39,10 +46,29 @@ impl Basket {
     (0..12).contains(&dx) && (0..6).contains(&dy) && ((dx != 0 && dx != 11) || dy != 0)
   }
 
+  /// The top left corner and size of the basket.
+  pub fn footprint(&self) -> ((i32, i32), (i32, i32)) {
+    (self.pos, SIZE)
+  }
+
   pub fn peaches_mut(&mut self) -> impl Iterator<Item = &mut Peach> {
     self.peaches.iter_mut()
   }
 
+  fn peaches_dyn_mut(&mut self) -> impl Iterator<Item = &mut dyn Entity> {
+    self
+      .peaches
+      .iter_mut()
+      .map(|peach| peach as &mut dyn Entity)
+  }
+
+  /// Moves the letters of the greeting the peaches burst into.
+  pub fn move_letters(&mut self, relayout: &Relayout) {
+    for peach in self.peaches.iter_mut() {
+      peach.move_letters(relayout);
+    }
+  }
+
   pub fn num_peaches(&self) -> usize {
     self.peaches.len()
   }
@@ -51,8 +77,15 @@ impl Basket {
     &mut self.peaches[idx]
   }
 
-  pub fn radiate(&mut self, pos: (i32, i32)) {
-    self.peaches_mut().for_each(|peach| peach.radiate(pos));
+  /// The tiles of the basket that things can't pass through.
+  pub fn solid_tiles(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+    self.sprite.draw(self.pos).map(|(_, pos)| pos)
+  }
+
+  pub fn simulate(&mut self, solids: &Solids, debris_solids: &Solids) {
+    self
+      .peaches_mut()
+      .for_each(|peach| peach.simulate(solids, debris_solids));
   }
 
   pub fn maybe_dunk(&mut self, water: &mut Water) {
@@ -61,28 +94,12 @@ impl Basket {
 }
 
 impl Entity for Basket {
-  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (crate::util::Draw, (i32, i32))> + '_> {
+  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
     Box::new(
-      BASKET
-        .iter()
-        .enumerate()
-        .flat_map(move |(dy, row)| {
-          let y = dy as i32 + self.pos.1;
-
-          row.chars().enumerate().filter_map(move |(dx, c)| {
-            let x = dx as i32 + self.pos.0;
-            if c == ' ' && !(1..=2).contains(&dy) {
-              return None;
-            }
-
-            Some((
-              Draw::new(c)
-                .with_fg(color::AnsiValue::rgb(2, 1, 0))
-                .with_z(if c == ' ' { BG_Z_IDX } else { Z_IDX }),
-              (x, y),
-            ))
-          })
-        })
+      self
+        .sprite
+        .draw(self.pos)
+        .chain(self.inside.draw(self.pos))
         .chain(self.peaches.iter().flat_map(|peach| peach.iterate_tiles())),
     )
   }
@@ -93,21 +110,39 @@ impl Entity for Basket {
     }
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    for peach in self.peaches.iter_mut() {
-      peach.click(x, y);
-    }
+  fn bounds(&self) -> Option<Bounds> {
+    Bounds::enclosing(self.peaches.iter().map(|peach| peach.bounds()))
+  }
+
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.peaches_dyn_mut(), (x, y), |peach| peach.click(x, y)).is_some()
+  }
+
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.peaches_dyn_mut(), (x, y), |peach| peach.drag(x, y)).is_some()
+  }
+
+  fn release(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.peaches_dyn_mut(), (x, y), |peach| peach.release(x, y)).is_some()
+  }
+
+  fn key(&mut self, key: Key) -> bool {
+    self.peaches.iter_mut().any(|peach| peach.key(key))
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
+  fn resize(&mut self, width: u32, height: u32) {
     for peach in self.peaches.iter_mut() {
-      peach.drag(x, y);
+      peach.resize(width, height);
     }
   }
 
-  fn release(&mut self, x: u32, y: u32) {
+  fn retune(&mut self, tuning: &Tuning) {
+    // Peaches still in the basket move with it.
+    let shift = (tuning.basket.0 - self.pos.0, tuning.basket.1 - self.pos.1);
+    self.pos = tuning.basket;
     for peach in self.peaches.iter_mut() {
-      peach.release(x, y);
+      peach.shift(shift);
+      peach.retune(tuning);
     }
   }
 }
@@ -1,80 +1,168 @@
 use std::{f32::consts::PI, sync::Mutex};
 
 use rand::{rngs::StdRng, seq::SliceRandom, Rng};
-use termion::color;
+use termion::event::Key;
 
 use crate::{
-  basket::Basket, carrot::Carrot, dialog::Dialog, entity::Entity, hole::Hole, landscape::Landscape,
-  train_scene::TrainScene, util::Draw, zoom::Zoom,
+  animation::{AnimationClip, Animator},
+  asset::Assets,
+  basket::{self, Basket},
+  carrot::{self, Carrot},
+  cue::{CueId, Cues},
+  dialog::Dialog,
+  entity::{dispatch, Bounds, Entity},
+  force::{Force, ForceFields},
+  greeting::{self, Greeting, Relayout},
+  hole::{self, Hole},
+  landscape::Landscape,
+  nav::NavGrid,
+  physics::Solids,
+  sprite::Sprite,
+  story::{CarrotAction, ClickFilter, Command, Pose, Story, Target},
+  train_scene::TrainScene,
+  tuning::Tuning,
+  tween::{Easing, Tween},
+  util::{Attrs, Draw},
+  zoom::Zoom,
 };
 
 const Z_IDX: i32 = 25;
-const STEP_PERIOD: usize = 10;
-
-const LETTERS: [(char, (i32, i32)); 20] = [
-  ('H', (60, 5)),
-  ('a', (61, 5)),
-  ('p', (62, 5)),
-  ('p', (63, 5)),
-  ('y', (64, 5)),
-  ('B', (59, 7)),
-  ('i', (60, 7)),
-  ('r', (61, 7)),
-  ('t', (62, 7)),
-  ('h', (63, 7)),
-  ('d', (64, 7)),
-  ('a', (65, 7)),
-  ('y', (66, 7)),
-  ('E', (60, 9)),
-  ('u', (61, 9)),
-  ('g', (62, 9)),
-  ('e', (63, 9)),
-  ('n', (64, 9)),
-  ('i', (65, 9)),
-  ('a', (66, 9)),
-];
+/// The width the bunny's art is mirrored within, so it turns around in place.
+const MIRROR_WIDTH: usize = 7;
+/// The space the bunny takes up when walking around.
+const BUNNY_SIZE: (i32, i32) = (MIRROR_WIDTH as i32, 4);
+/// How far the shockwave of the kazoo spreads, at a column per tick.
+const SHREEK_RADIUS: i32 = 75;
 
+/// The window size that tuned positions are given for.
+const LAYOUT_SIZE: (i32, i32) = (120, 40);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum BunnyState {
   Sleep,
   Wake,
   Walk1,
   Walk2,
-  Blink { t: usize },
+  Blink,
   HoldKazoo,
   BlowKazoo,
   Munch,
 }
 
-enum Direction {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
   Left,
   Right,
 }
 
-#[derive(PartialEq, Eq)]
-enum BunnyStage {
-  Sleep1,
-  // Wake up, ask for help finding carrot.
-  Speak1 { t: usize, dialog_idx: u32 },
-  AwaitDecision1,
-  AwaitDecisionBasket,
-  WalkToBasket { t: usize, init_pos: (i32, i32) },
-  BasketDialog { t: usize, dialog_idx: u32 },
-  AwaitPeachDestruction { t: usize, rem_peaches: u32 },
-  PeachesHaveNoCarrots { t: usize, dialog_idx: u32 },
-  AwaitDecisionHole,
-  WalkToHole { t: usize, init_pos: (i32, i32) },
-  HoleDialog { t: usize, dialog_idx: u32 },
-  Dig,
-  HoleHasNoCarrots { t: usize, dialog_idx: u32 },
-  WalkToKazoo { t: usize, init_pos: (i32, i32) },
-  PlayKazoo { t: usize },
-  WalkToCarrot { t: usize, init_pos: (i32, i32) },
-  EatCarrot { t: usize, dialog_idx: u32 },
+/// The art for each state of the bunny, facing left and right. The art is
+/// drawn facing left, and mirrored to face right.
+struct BunnySprites {
+  sleep: (Sprite, Sprite),
+  wake: (Sprite, Sprite),
+  walk1: (Sprite, Sprite),
+  walk2: (Sprite, Sprite),
+  blink: (Sprite, Sprite),
+  hold_kazoo: (Sprite, Sprite),
+  blow_kazoo: (Sprite, Sprite),
+  munch: (Sprite, Sprite),
+}
+
+impl BunnySprites {
+  fn new(assets: &Assets) -> Self {
+    let facing = |name| {
+      let left = assets
+        .get(name)
+        .sprite()
+        .with_width(MIRROR_WIDTH)
+        .with_z(Z_IDX);
+      let right = left.mirrored();
+      (left, right)
+    };
+    Self {
+      sleep: facing("bunny_sleep"),
+      wake: facing("bunny_wake"),
+      walk1: facing("bunny_step1"),
+      walk2: facing("bunny_step2"),
+      blink: facing("bunny_blink"),
+      hold_kazoo: facing("bunny_hold_kazoo"),
+      blow_kazoo: facing("bunny_blow_kazoo"),
+      munch: facing("bunny_munch"),
+    }
+  }
+
+  fn get(&self, state: &BunnyState, direction: Direction) -> &Sprite {
+    let (left, right) = match state {
+      BunnyState::Sleep => &self.sleep,
+      BunnyState::Wake => &self.wake,
+      BunnyState::Walk1 => &self.walk1,
+      BunnyState::Walk2 => &self.walk2,
+      BunnyState::Blink => &self.blink,
+      BunnyState::HoldKazoo => &self.hold_kazoo,
+      BunnyState::BlowKazoo => &self.blow_kazoo,
+      BunnyState::Munch => &self.munch,
+    };
+    match direction {
+      Direction::Left => left,
+      Direction::Right => right,
+    }
+  }
+}
+
+/// What the bunny is doing for the story command it is blocked on.
+enum Progress {
+  Wait,
+  Walk { path: Tween<(i32, i32)> },
+  Peaches { rem_peaches: u32 },
+}
+
+/// Something the bunny does at a set time.
+enum BunnyCue {
+  /// Move on from the blocking story command.
+  Continue,
+  /// Start eating the carrot, taking a bite every `period`.
+  Munch {
+    period: usize,
+  },
+  Run(Command),
+}
+
+/// Something the bunny does when one of its clips reaches the end.
+#[derive(Clone, Copy)]
+enum ClipEvent {
+  /// Stop blinking.
+  OpenEyes,
+  /// Take a bite of the carrot.
+  Bite,
+}
+
+enum StoryEvent {
+  Tick,
+  Click((i32, i32)),
+  Key(Key),
+}
+
+/// `tuning` for a `width` x `height` window. Positions are tuned for a 120x40
+/// window, so they are moved in proportion, keeping everything inside.
+fn fit_tuning(tuning: &Tuning, width: u32, height: u32) -> Tuning {
+  let (width, height) = (width as i32, height as i32);
+  let fit = |(x, y): (i32, i32), (w, h): (i32, i32)| {
+    (
+      (x * width / LAYOUT_SIZE.0).clamp(0, (width - w).max(0)),
+      (y * height / LAYOUT_SIZE.1).clamp(0, (height - h).max(0)),
+    )
+  };
+  Tuning {
+    basket: fit(tuning.basket, basket::SIZE),
+    hole: fit(tuning.hole, hole::SIZE),
+    carrot: fit(tuning.carrot, carrot::SIZE),
+    ..*tuning
+  }
 }
 
 pub struct Bunny<'a> {
-  state: BunnyState,
-  stage: BunnyStage,
+  animator: Animator<BunnyState, ClipEvent>,
+  sprites: BunnySprites,
   direction: Direction,
   dialog: Option<Dialog>,
   pos: (i32, i32),
@@ -85,238 +173,501 @@ pub struct Bunny<'a> {
   hole: Hole,
   carrot: Carrot,
   zoom: Zoom,
+  /// The index in `children_mut` of the child that last took a click or drag,
+  /// which is offered keys before the others.
+  focus: Option<usize>,
+  /// The force fields acting on debris and peaches.
+  forces: ForceFields,
+  /// Ticks the bunny takes per step.
+  step_period: usize,
+  /// The tuning as given, for a 120x40 window.
+  tuning: Tuning,
+  size: (u32, u32),
+  story: Story,
+  /// The index of the story command being run.
+  pc: usize,
+  /// When the last blocking story command finished.
+  story_t: usize,
+  progress: Progress,
+  /// Whether clicking the bunny makes it blink.
+  blink: bool,
+  cues: Cues<BunnyCue>,
+  /// When the bunny starts munching, if it hasn't yet.
+  munch_cue: Option<CueId>,
   completed_activities: u32,
+  message: String,
+  greeting: Greeting,
+  /// The letters of the greeting that haven't been revealed yet.
   unused_letters: Vec<usize>,
+  /// How many times part of the greeting is revealed, and how many of those
+  /// have happened.
+  num_reveals: usize,
+  reveals: usize,
   rng: &'a mut StdRng,
   done: &'a Mutex<bool>,
 }
 
 impl<'a> Bunny<'a> {
-  pub fn new(width: u32, height: u32, rng: &'a mut StdRng, done: &'a Mutex<bool>) -> Self {
-    let landscape = Landscape::new(width, height, rng);
+  pub fn new(
+    width: u32,
+    height: u32,
+    message: &str,
+    story: Story,
+    assets: &Assets,
+    rng: &'a mut StdRng,
+    done: &'a Mutex<bool>,
+  ) -> Self {
+    let tuning = fit_tuning(&Tuning::default(), width, height);
+    let landscape = Landscape::new(width, height, &tuning, rng);
+    let greeting = greeting::layout(message, width, height);
     let pos = (width as i32 / 2 - 10, height as i32 / 2 - 10);
-    Self {
-      state: BunnyState::Sleep,
-      stage: BunnyStage::Sleep1,
+    let forces = ForceFields::new();
+    let mut bunny = Self {
+      animator: Animator::new(BunnyState::Sleep),
+      sprites: BunnySprites::new(assets),
       direction: Direction::Right,
       dialog: None,
       pos,
       t: 0,
       landscape,
-      basket: Basket::new((9, 10)),
-      train_scene: TrainScene::new(width, height),
-      hole: Hole::new((102, 12)),
-      carrot: Carrot::new((30, 14)),
+      basket: Basket::new(tuning.basket, assets, &forces),
+      train_scene: TrainScene::new(width, height, assets, &tuning),
+      hole: Hole::new(tuning.hole, assets, &forces),
+      carrot: Carrot::new(tuning.carrot, &forces),
       zoom: Zoom::new(width, height),
+      focus: None,
+      forces,
+      step_period: tuning.step_period,
+      tuning: Tuning::default(),
+      size: (width, height),
+      story,
+      pc: 0,
+      story_t: 0,
+      progress: Progress::Wait,
+      blink: false,
+      cues: Cues::new(),
+      munch_cue: None,
       completed_activities: 0,
-      unused_letters: (0..20).collect(),
+      unused_letters: (0..greeting.letters.len()).collect(),
+      greeting,
+      message: message.to_string(),
+      num_reveals: 0,
+      reveals: 0,
       rng,
       done,
+    };
+    bunny.num_reveals = bunny.story.num_reveals(bunny.basket.num_peaches());
+    bunny.run_story(0);
+    bunny
+  }
+
+  /// Where the bunny can walk: anywhere but the water, the basket, the hole
+  /// and the track.
+  fn nav_grid(&self) -> NavGrid {
+    let mut grid = self.landscape.nav_grid();
+    for (pos, size) in [
+      self.basket.footprint(),
+      self.hole.footprint(),
+      self.train_scene.track().footprint(),
+    ] {
+      grid.block_rect(pos, size);
     }
+    grid
   }
 
-  fn dt_to_completion(&self, init_pos: (i32, i32), target_pos: (i32, i32)) -> usize {
-    let dx = target_pos.0 - init_pos.0;
-    let dy = target_pos.1 - init_pos.1;
-    STEP_PERIOD * (dx.unsigned_abs() as usize + 2 * dy.unsigned_abs() as usize)
+  /// What debris bounces off of: the bunny, the basket, the train and the
+  /// edges of the window.
+  fn solids(&self) -> Solids {
+    let mut solids = self.landscape.solids();
+    let bunny = self.sprites.get(self.animator.frame(), self.direction);
+    let train = self.train_scene.train().iterate_tiles();
+    for pos in bunny
+      .draw(self.pos)
+      .chain(train)
+      .map(|(_, pos)| pos)
+      .chain(self.basket.solid_tiles())
+    {
+      solids.fill(pos);
+    }
+    solids
   }
 
-  fn interpolate_pos(&mut self, dt: usize, init_pos: (i32, i32), target_pos: (i32, i32)) {
-    if dt % STEP_PERIOD != 0 {
-      return;
+  /// The path from where the bunny is over to `target_pos`, around anything in
+  /// the way, taking a step period per column, or per half a row. If there is
+  /// no way there, the bunny stays where it is.
+  fn walk_path(&self, target_pos: (i32, i32)) -> Tween<(i32, i32)> {
+    let corners = self
+      .nav_grid()
+      .find_path(self.pos, target_pos, BUNNY_SIZE)
+      .unwrap_or_else(|| vec![self.pos]);
+    corners.windows(2).fold(Tween::new(self.pos), |path, leg| {
+      let dx = (leg[1].0 - leg[0].0) as f32;
+      let dy = 2. * (leg[1].1 - leg[0].1) as f32;
+      let ticks = (self.step_period as f32 * dx.hypot(dy)).round() as usize;
+      path.then(leg[1], ticks, Easing::Linear)
+    })
+  }
+
+  /// The children that take mouse events, with the basket last.
+  fn children_mut(&mut self) -> Vec<&mut dyn Entity> {
+    vec![&mut self.landscape, &mut self.train_scene, &mut self.basket]
+  }
+
+  /// Lays the greeting out again for a `width` x `height` window, moving the
+  /// letters that have been revealed along with it.
+  fn relayout_greeting(&mut self, width: u32, height: u32) {
+    let greeting = greeting::layout(&self.message, width, height);
+    let relayout = Relayout::new(&self.greeting, &greeting);
+    self.hole.move_letters(&relayout);
+    self.basket.move_letters(&relayout);
+    self.carrot.move_letters(&relayout);
+
+    let (old_len, new_len) = (self.greeting.letters.len(), greeting.letters.len());
+    self.unused_letters.retain(|&idx| idx < new_len);
+    self.unused_letters.extend(old_len..new_len);
+    self.greeting = greeting;
+  }
+
+  /// `offset` from the top left corner of `from`, or from the origin.
+  fn place(&self, from: Option<Target>, offset: (i32, i32)) -> (i32, i32) {
+    let origin = match from {
+      None => (0, 0),
+      Some(Target::Bunny) => self.pos,
+      Some(Target::Basket) => self.basket.footprint().0,
+      Some(Target::Hole) => self.hole.footprint().0,
+      Some(Target::Carrot) => self.carrot.footprint().0,
+    };
+    (origin.0 + offset.0, origin.1 + offset.1)
+  }
+
+  fn is_on(&self, target: Target, (x, y): (i32, i32)) -> bool {
+    match target {
+      Target::Bunny => {
+        self.pos.0 <= x && x < self.pos.0 + 8 && self.pos.1 <= y && y < self.pos.1 + 4
+      }
+      Target::Basket => self.basket.contains_click((x, y)),
+      Target::Hole => self.hole.contains_click((x, y)),
+      Target::Carrot => self.carrot.contains_click((x, y)),
+    }
+  }
+
+  /// Runs story commands starting at `pc`, up to the next one that blocks.
+  fn run_story(&mut self, mut pc: usize) {
+    self.story_t = self.t;
+    self.progress = Progress::Wait;
+
+    while let Some(command) = self.story.command(pc).cloned() {
+      self.pc = pc;
+      pc += 1;
+      match command {
+        Command::Wait(dt) => {
+          self.cues.at(self.story_t + dt, BunnyCue::Continue);
+          return;
+        }
+        Command::WaitClick(_) | Command::Choose(_) => return,
+        Command::Walk(from, offset) => {
+          let target = self.place(from, offset);
+          self.direction = if target.0 < self.pos.0 {
+            Direction::Left
+          } else {
+            Direction::Right
+          };
+          self.progress = Progress::Walk {
+            path: self.walk_path(target),
+          };
+          let cycle = AnimationClip::looping(vec![
+            (BunnyState::Walk1, self.step_period),
+            (BunnyState::Walk2, self.step_period),
+          ]);
+          self.animator.play(cycle, None);
+          return;
+        }
+        Command::Peaches => {
+          self.progress = Progress::Peaches {
+            rem_peaches: self.remaining_peaches(),
+          };
+          return;
+        }
+        Command::Dig => {
+          let mut letters: Vec<_> = self
+            .random_guaranteed_letters()
+            .into_iter()
+            .map(|letter| (true, letter.0, letter.1))
+            .chain((0..200).map(|_| {
+              let dr = self.rng.gen_range(5.0..50.);
+              let dt = self.rng.gen_range(PI * 0.75..PI * 1.25);
+              let dx = (dt.cos() * dr) as i32;
+              let dy = (dt.sin() * dr) as i32;
+              let letter = self.rng.gen_range('a'..='z');
+              (false, letter, (self.pos.0 + 2 + dx, self.pos.1 + 2 + dy))
+            }))
+            .collect();
+          letters.shuffle(self.rng);
+          self.hole.add_dirt(letters);
+          return;
+        }
+        Command::Goto(target) => pc = target,
+        Command::IfCompleted(completed, target) => {
+          if self.completed_activities == completed {
+            pc = target;
+          }
+        }
+        command => self.act(command),
+      }
     }
-    let step_num = dt / STEP_PERIOD;
-
-    let dx = target_pos.0 - init_pos.0;
-    let dy = target_pos.1 - init_pos.1;
-    if step_num <= dx.unsigned_abs() as usize {
-      if dx < 0 {
-        self.direction = Direction::Left;
-      } else {
-        self.direction = Direction::Right;
+    self.pc = pc;
+  }
+
+  /// Carries out a story command that neither blocks nor jumps.
+  fn act(&mut self, command: Command) {
+    match command {
+      Command::Pose(pose) => {
+        self.animator.show(match pose {
+          Pose::Sleep => BunnyState::Sleep,
+          Pose::Wake => BunnyState::Wake,
+          Pose::Stand => BunnyState::Walk1,
+          Pose::Step => BunnyState::Walk2,
+          Pose::Blink => BunnyState::Blink,
+          Pose::Kazoo => BunnyState::HoldKazoo,
+          Pose::Blow => BunnyState::BlowKazoo,
+          Pose::Munch => BunnyState::Munch,
+        });
+        if pose == Pose::Blink {
+          self.blink_eyes();
+        }
+      }
+      Command::Face(direction) => self.direction = direction,
+      Command::Say {
+        dx,
+        to_left,
+        bold,
+        text,
+      } => {
+        self.dialog = Some(
+          Dialog::new((self.pos.0 + dx, self.pos.1), text, to_left).with_attrs(Attrs {
+            bold,
+            ..Attrs::default()
+          }),
+        );
+      }
+      Command::Hide => self.dialog = None,
+      Command::Blink(blink) => self.blink = blink,
+      Command::Complete => self.completed_activities += 1,
+      Command::Train { frozen: true } => self.train_scene.freeze(),
+      Command::Train { frozen: false } => self.train_scene.unfreeze(),
+      Command::Kazoo(pos) => self.hole.set_kazoo_pos(pos),
+      Command::Shreek(pos) => {
+        self.landscape.shreek(pos);
+        self.forces.add(
+          self.t,
+          Force::Shockwave {
+            center: pos,
+            radius: SHREEK_RADIUS,
+            ticks: SHREEK_RADIUS as usize,
+            easing: Easing::Linear,
+          },
+        );
       }
-      if step_num % 2 != 0 {
-        self.pos = (init_pos.0 + step_num as i32 * dx.signum(), init_pos.1);
+      Command::Force(force) => self.forces.add(self.t, force),
+      Command::Carrot(action) => match action {
+        CarrotAction::Appear => self.carrot.appear(),
+        CarrotAction::Flip => self.carrot.make_upside_down(),
+        CarrotAction::Behead => self.carrot.delete_head(),
+        CarrotAction::Reveal => {
+          let letters = self.random_guaranteed_letters();
+          self.carrot.set_target_letters(letters);
+        }
+        CarrotAction::Move(from, offset) => self.carrot.set_pos(self.place(from, offset)),
+      },
+      Command::Munch { delay, period } => {
+        // Munching again before starting replaces when and how fast.
+        if let Some(cue) = self.munch_cue {
+          self.cues.cancel(cue);
+        }
+        self.munch_cue = Some(self.cues.at(self.t + delay, BunnyCue::Munch { period }));
       }
-      self.state = if step_num % 2 == 0 {
-        BunnyState::Walk1
-      } else {
-        BunnyState::Walk2
-      };
-    } else if step_num <= (dx.unsigned_abs() + 2 * dy.unsigned_abs()) as usize {
-      if dx < 0 {
-        self.direction = Direction::Left;
-      } else {
-        self.direction = Direction::Right;
+      Command::After(dt, command) => {
+        self.cues.at(self.t + dt, BunnyCue::Run(*command));
       }
-      self.state = if (dx < 0) ^ (step_num % 2 == 0) {
-        BunnyState::Walk2
-      } else {
-        BunnyState::Walk1
-      };
-      self.pos = (
-        target_pos.0,
-        init_pos.1 + ((step_num as i32 - dx.abs()) / 2) * dy.signum(),
-      );
+      Command::Zoom(pos, radius) => self.zoom.zoom(pos, radius),
+      Command::Zoom2(pos, radius) => self.zoom.zoom2(pos, radius),
+      Command::ZoomGreeting => {
+        // Just wide enough to show every letter, with rows stretched to be as
+        // tall as columns are wide.
+        let ((x, y), (width, height)) = self.greeting.bounds;
+        let radius = (width as f32 / 2.).hypot(height as f32 * 11. / 10.);
+        self
+          .zoom
+          .zoom((x + width / 2, y + height / 2), radius.ceil() as u32 + 1);
+      }
+      Command::ZoomOut => self.zoom.disappear(),
+      Command::End => *self.done.lock().unwrap() = true,
+      Command::Wait(_)
+      | Command::WaitClick(_)
+      | Command::Choose(_)
+      | Command::Walk(..)
+      | Command::Goto(_)
+      | Command::IfCompleted(..)
+      | Command::Peaches
+      | Command::Dig => unreachable!("{command:?} is run by run_story"),
     }
   }
 
-  fn random_guaranteed_letters(&mut self) -> Vec<(char, (i32, i32))> {
-    const NUM_TARGETS: u32 = 6;
-    const TOTAL_LEN: u32 = LETTERS.len() as u32;
+  /// Closes the bunny's eyes for a moment.
+  fn blink_eyes(&mut self) {
+    self.animator.play(
+      AnimationClip::once(vec![(BunnyState::Blink, 6)]),
+      Some(ClipEvent::OpenEyes),
+    );
+  }
+
+  /// Moves the bunny's animation on to `t`, and reacts to the clips that
+  /// finished.
+  fn run_animation(&mut self, t: usize) {
+    for event in self.animator.tick(t) {
+      match event {
+        ClipEvent::OpenEyes => {
+          if self.blink {
+            self.animator.show(BunnyState::Walk1);
+          }
+        }
+        ClipEvent::Bite => self.carrot.scatter(self.rng),
+      }
+    }
+  }
+
+  /// Fires the cues due by now, and returns whether the story moved on.
+  fn run_cues(&mut self) -> bool {
+    let mut continued = false;
+    while let Some(cue) = self.cues.pop_due(self.t) {
+      match cue {
+        BunnyCue::Continue => {
+          continued = true;
+          self.run_story(self.pc + 1);
+        }
+        BunnyCue::Munch { period } => {
+          self.carrot.scatter(self.rng);
+          // Bite with the mouth open for the first half, and chew for the
+          // rest.
+          let clip = AnimationClip::looping(vec![
+            (BunnyState::Walk1, period / 2),
+            (BunnyState::Munch, period - period / 2),
+          ]);
+          self.animator.play(clip, Some(ClipEvent::Bite));
+        }
+        BunnyCue::Run(command) => self.act(command),
+      }
+    }
+    continued
+  }
+
+  /// Checks whether the blocking story command is done after `event`, and if
+  /// so, returns the index of the command to run next.
+  fn poll_story(&mut self, event: StoryEvent) -> Option<usize> {
+    let next = self.pc + 1;
+    match (self.story.command(self.pc)?.clone(), event) {
+      (Command::WaitClick(filter), StoryEvent::Click(pos)) => match filter {
+        ClickFilter::Any => Some(next),
+        ClickFilter::On(target) => self.is_on(target, pos).then_some(next),
+        ClickFilter::Outside(target) => (!self.is_on(target, pos)).then_some(next),
+      },
+      (Command::Choose(options), StoryEvent::Click(pos)) => options
+        .into_iter()
+        .find(|&(target, _)| self.is_on(target, pos))
+        .map(|(_, label)| label),
+      (Command::WaitClick(_), StoryEvent::Key(Key::Char(' ' | '\n'))) => Some(next),
+      (Command::Choose(options), StoryEvent::Key(Key::Char(digit))) => {
+        let idx = (digit.to_digit(10)? as usize).checked_sub(1)?;
+        options.get(idx).map(|&(_, label)| label)
+      }
+      (Command::Walk(..), StoryEvent::Tick) => {
+        let Progress::Walk { path } = &self.progress else {
+          unreachable!()
+        };
+        let dt = self.t - self.story_t;
+        let pos = path.value_at(dt);
+        if pos.0 != self.pos.0 {
+          self.direction = if pos.0 < self.pos.0 {
+            Direction::Left
+          } else {
+            Direction::Right
+          };
+        }
+        self.pos = pos;
+        (dt > path.duration()).then(|| {
+          self.animator.show(BunnyState::Walk1);
+          next
+        })
+      }
+      (Command::Peaches, StoryEvent::Tick) => {
+        let Progress::Peaches { rem_peaches } = self.progress else {
+          unreachable!()
+        };
+        let peaches = self.remaining_peaches();
+        if peaches != rem_peaches {
+          self.progress = Progress::Peaches {
+            rem_peaches: peaches,
+          };
+          if peaches == 0 {
+            self.cues.at(self.t + 100, BunnyCue::Continue);
+          }
+        }
 
-    let idx = (self.unused_letters.len() + 1) as u32 * NUM_TARGETS / TOTAL_LEN;
-    let num_to_take = (idx * TOTAL_LEN / NUM_TARGETS) - ((idx - 1) * TOTAL_LEN / NUM_TARGETS);
+        for peach_idx in 0..self.basket.num_peaches() {
+          if !self.basket.peach_at_mut(peach_idx).exploded()
+            && self
+              .train_scene
+              .train()
+              .collides_with_front(self.basket.peach_at_mut(peach_idx).hitbox())
+          {
+            let letters = self.random_guaranteed_letters();
+            self
+              .basket
+              .peach_at_mut(peach_idx)
+              .explode(letters, self.rng);
+          }
+        }
+        None
+      }
+      (Command::Dig, StoryEvent::Click(pos)) => {
+        (self.hole.contains_click(pos) && !(0..10).all(|_| self.hole.fling())).then_some(next)
+      }
+      _ => None,
+    }
+  }
+
+  fn remaining_peaches(&mut self) -> u32 {
+    self
+      .basket
+      .peaches_mut()
+      .filter(|peach| !peach.exploded())
+      .count() as u32
+  }
+
+  fn random_guaranteed_letters(&mut self) -> Vec<(char, (i32, i32))> {
+    // Split the message as evenly as possible, with the last reveal taking
+    // whatever is left.
+    let remaining = self.unused_letters.len();
+    let reveals_left = self.num_reveals.saturating_sub(self.reveals);
+    self.reveals += 1;
+    let keep =
+      reveals_left.saturating_sub(1) * self.greeting.letters.len() / self.num_reveals.max(1);
+    let num_to_take = remaining.saturating_sub(keep);
     (0..num_to_take)
       .map(|_| {
         let rand_idx = self.rng.gen_range(0..self.unused_letters.len());
-        LETTERS[self.unused_letters.remove(rand_idx)]
+        self.greeting.letters[self.unused_letters.remove(rand_idx)]
       })
       .collect()
   }
 }
 
-#[rustfmt::skip]
-const RIGHT_SLEEP: [&str; 3] = [
-  r#"(\(\"#,
-  r#"(-.-)"#,
-  r#"o_(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_SLEEP: [&str; 3] = [
-  r#"    /)/)"#,
-  r#"   (-.-)"#,
-  r#"(")(")_o"#,
-];
-
-#[rustfmt::skip]
-const RIGHT_WAKE: [&str; 3] = [
-  r#"(\(\"#,
-  r#"(o.o)"#,
-  r#"o_(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_WAKE: [&str; 3] = [
-  r#"    /)/)"#,
-  r#"   (o.o)"#,
-  r#"(")(")_o"#,
-];
-
-#[rustfmt::skip]
-const RIGHT_STEP1: [&str; 4] = [
-  r#" (\(\"#,
-  r#" (o.o)"#,
-  r#" (>_<)"#,
-  r#" (")(")"#,
-];
-
-#[rustfmt::skip]
-const RIGHT_STEP2: [&str; 4] = [
-  r#" (\(\"#,
-  r#" (o.o)"#,
-  r#" (>_<)"#,
-  r#"(") (")"#,
-];
-
-#[rustfmt::skip]
-const RIGHT_BLINK: [&str; 4] = [
-  r#" (\/)"#,
-  r#" (>.<)"#,
-  r#" (>_<)"#,
-  r#" (")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_STEP1: [&str; 4] = [
-  r#"  /)/)"#,
-  r#" (o.o)"#,
-  r#" (>_<)"#,
-  r#"(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_STEP2: [&str; 4] = [
-  r#"  /)/)"#,
-  r#" (o.o)"#,
-  r#" (>_<)"#,
-  r#"(") (")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_BLINK: [&str; 4] = [
-  r#"  (\/)"#,
-  r#" (>.<)"#,
-  r#" (>_<)"#,
-  r#"(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_HOLD_KAZOO: [&str; 4] = [
-  r#"  /)/)"#,
-  r#" (o.o)"#,
-  r#"<(__<)"#,
-  r#"(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_HOLD_KAZOO_BLOW: [&str; 4] = [
-  r#"  /)/)"#,
-  r#" (-o-)"#,
-  r#"<(__<)"#,
-  r#"(")(")"#,
-];
-
-#[rustfmt::skip]
-const LEFT_MUNCH: [&str; 4] = [
-  r#"  /)/)"#,
-  r#" (o0o)"#,
-  r#" (>_<)"#,
-  r#"(")(")"#,
-];
-
 impl<'a> Entity for Bunny<'a> {
   fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
-    let bunny_str: &[&str] = match (&self.state, &self.direction) {
-      (BunnyState::Sleep, Direction::Left) => &LEFT_SLEEP,
-      (BunnyState::Sleep, Direction::Right) => &RIGHT_SLEEP,
-      (BunnyState::Wake, Direction::Left) => &LEFT_WAKE,
-      (BunnyState::Wake, Direction::Right) => &RIGHT_WAKE,
-      (BunnyState::Walk1, Direction::Left) => &LEFT_STEP1,
-      (BunnyState::Walk1, Direction::Right) => &RIGHT_STEP1,
-      (BunnyState::Walk2, Direction::Left) => &LEFT_STEP2,
-      (BunnyState::Walk2, Direction::Right) => &RIGHT_STEP2,
-      (BunnyState::Blink { t: _ }, Direction::Left) => &LEFT_BLINK,
-      (BunnyState::Blink { t: _ }, Direction::Right) => &RIGHT_BLINK,
-      (BunnyState::HoldKazoo, Direction::Left) => &LEFT_HOLD_KAZOO,
-      (BunnyState::HoldKazoo, Direction::Right) => unreachable!(),
-      (BunnyState::BlowKazoo, Direction::Left) => &LEFT_HOLD_KAZOO_BLOW,
-      (BunnyState::BlowKazoo, Direction::Right) => unreachable!(),
-      (BunnyState::Munch, Direction::Left) => &LEFT_MUNCH,
-      (BunnyState::Munch, Direction::Right) => unreachable!(),
-    };
-
-    let bunny_iter = bunny_str
-      .iter()
-      .enumerate()
-      .flat_map(move |(y, row)| {
-        let y = y as i32 + self.pos.1;
-
-        row.chars().enumerate().filter_map(move |(x, c)| {
-          let x = x as i32 + self.pos.0;
-          if c == ' ' {
-            return None;
-          }
-
-          Some((
-            Draw::new(c)
-              .with_fg(color::AnsiValue::grayscale(22))
-              .with_z(Z_IDX),
-            (x, y),
-          ))
-        })
-      })
+    let bunny_iter = self
+      .sprites
+      .get(self.animator.frame(), self.direction)
+      .draw(self.pos)
       .chain(self.landscape.iterate_tiles())
       .chain(self.basket.iterate_tiles())
       .chain(self.train_scene.iterate_tiles())
@@ -337,641 +688,117 @@ impl<'a> Entity for Bunny<'a> {
     self.carrot.tick(t);
     self.zoom.tick(t);
 
+    // Bits of debris catch on the rails, but whole peaches sit on them.
+    let solids = self.solids();
+    let mut debris_solids = solids.clone();
+    for (_, pos) in self.train_scene.track().iterate_tiles() {
+      debris_solids.fill(pos);
+    }
+    self.hole.simulate(&debris_solids);
+    self.basket.simulate(&solids, &debris_solids);
     self.hole.maybe_dunk(self.landscape.water_mut());
     self.basket.maybe_dunk(self.landscape.water_mut());
 
     self.landscape.tick(t);
     self.t = t;
+    self.run_animation(t);
 
-    match self.stage {
-      BunnyStage::Sleep1 => {}
-      BunnyStage::Speak1 {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-
-        match dialog_idx {
-          0 => {
-            if dt == 50 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 5, self.pos.1),
-                "Oh! Hello there!".to_string(),
-                false,
-              ));
-            }
-          }
-          1 => {
-            if dt == 50 {
-              self.state = BunnyState::Walk1;
-            } else if dt == 100 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 6, self.pos.1),
-                "I am so hungry, and my favorite food is carrots.".to_string(),
-                false,
-              ));
-            }
-          }
-          2 => {
-            if dt == 10 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 6, self.pos.1),
-                "Would you help me find a carrot?".to_string(),
-                false,
-              ));
-              self.stage = BunnyStage::AwaitDecision1;
-            }
-          }
-          _ => unreachable!(),
-        }
-      }
-      BunnyStage::AwaitDecision1 => {
-        if let BunnyState::Blink { t: initial_t } = self.state {
-          let dt = t - initial_t;
-          if dt == 6 {
-            self.state = BunnyState::Walk1;
-          }
-        }
-      }
-      BunnyStage::AwaitDecisionBasket => {
-        if let BunnyState::Blink { t: initial_t } = self.state {
-          let dt = t - initial_t;
-          if dt == 6 {
-            self.state = BunnyState::Walk1;
-          }
-        }
-      }
-      BunnyStage::WalkToBasket {
-        t: initial_t,
-        init_pos,
-      } => {
-        const TARGET: (i32, i32) = (22, 11);
-        let dt = t - initial_t;
-        if dt > self.dt_to_completion(init_pos, TARGET) {
-          self.stage = BunnyStage::BasketDialog { t, dialog_idx: 0 };
-          self.state = BunnyState::Walk1;
-        } else {
-          self.interpolate_pos(t - initial_t, init_pos, TARGET);
-        }
+    if !self.run_cues() {
+      if let Some(next) = self.poll_story(StoryEvent::Tick) {
+        self.run_story(next);
       }
-      BunnyStage::BasketDialog {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-
-        match dialog_idx {
-          0 => {
-            if dt == 50 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 7, self.pos.1),
-                "Why, this basket seems to be full of peaches!".to_string(),
-                false,
-              ));
-            }
-          }
-          1 => {
-            if dt == 10 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 7, self.pos.1),
-                "It's a shame that I don't like peaches. Maybe if I can \
-                 figure out how to open a peach, there will be a carrot inside."
-                  .to_string(),
-                false,
-              ));
-            }
-          }
-          2 => {
-            if dt == 10 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 7, self.pos.1),
-                "Hey, are those train tracks? Maybe if the peaches collide \
-                 with the nose of a passing train, they will open!"
-                  .to_string(),
-                false,
-              ));
-            }
-          }
-          _ => unreachable!(),
-        }
-      }
-      BunnyStage::AwaitPeachDestruction {
-        t: peach_t,
-        rem_peaches,
-      } => {
-        if let BunnyState::Blink { t: initial_t } = self.state {
-          let dt = t - initial_t;
-          if dt == 6 {
-            self.state = BunnyState::Walk1;
-          }
-        }
-        let peaches = self
-          .basket
-          .peaches_mut()
-          .filter(|peach| !peach.exploded())
-          .count();
-        if peaches as u32 != rem_peaches {
-          self.stage = BunnyStage::AwaitPeachDestruction {
-            t: self.t,
-            rem_peaches: peaches as u32,
-          };
-        }
-        if rem_peaches == 0 && t == peach_t + 100 {
-          self.stage = BunnyStage::PeachesHaveNoCarrots {
-            t: self.t,
-            dialog_idx: 0,
-          };
-          self.train_scene.freeze();
-          self.completed_activities += 1;
-        }
+    }
+  }
 
-        for peach_idx in 0..self.basket.num_peaches() {
-          if !self.basket.peach_at_mut(peach_idx).exploded()
-            && self
-              .train_scene
-              .train()
-              .collides_with_front(self.basket.peach_at_mut(peach_idx).hitbox())
-          {
-            let letters = self.random_guaranteed_letters();
-            self
-              .basket
-              .peach_at_mut(peach_idx)
-              .explode(letters, self.rng);
-          }
-        }
-      }
-      BunnyStage::PeachesHaveNoCarrots {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-
-        match dialog_idx {
-          0 => {
-            if dt == 1 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 7, self.pos.1),
-                "Whelp, those peaches didn't have any carrots inside...".to_string(),
-                false,
-              ));
-            }
-          }
-          1 => {
-            if dt == 10 {
-              if self.completed_activities == 2 {
-                self.dialog = Some(Dialog::new(
-                  (self.pos.0 + 7, self.pos.1),
-                  "Maybe if I go play that kazoo, I will find a carrot.".to_string(),
-                  false,
-                ));
-              } else {
-                self.dialog = Some(Dialog::new(
-                  (self.pos.0 + 7, self.pos.1),
-                  "Could there be a carrot hidden somewhere else?".to_string(),
-                  false,
-                ));
-              }
-            }
-          }
-          _ => unreachable!(),
-        }
-      }
-      BunnyStage::AwaitDecisionHole => {
-        if let BunnyState::Blink { t: initial_t } = self.state {
-          let dt = t - initial_t;
-          if dt == 6 {
-            self.state = BunnyState::Walk1;
-          }
-        }
-      }
-      BunnyStage::WalkToHole {
-        t: initial_t,
-        init_pos,
-      } => {
-        const TARGET: (i32, i32) = (95, 12);
-        let dt = t - initial_t;
-        if dt > self.dt_to_completion(init_pos, TARGET) {
-          self.stage = BunnyStage::HoleDialog { t, dialog_idx: 0 };
-          self.state = BunnyState::Walk1;
-        } else {
-          self.interpolate_pos(t - initial_t, init_pos, TARGET);
-        }
-      }
-      BunnyStage::HoleDialog {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-
-        match dialog_idx {
-          0 => {
-            if dt == 50 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 - 2, self.pos.1),
-                "A hole?? Maybe there's a carrot in here!".to_string(),
-                true,
-              ));
-            }
-          }
-          1 => {
-            if dt == 10 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 - 2, self.pos.1),
-                "Could you help me dig? Try clicking on the hole really really fast!".to_string(),
-                true,
-              ));
-            }
-          }
-          _ => unreachable!(),
-        }
-      }
-      BunnyStage::Dig => {}
-      BunnyStage::HoleHasNoCarrots {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-
-        match dialog_idx {
-          0 => {
-            if dt == 50 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 - 2, self.pos.1),
-                "Whelp, that hole didn't seem to have any carrots, but I think \
-                 I saw a red kazoo fly out..."
-                  .to_string(),
-                true,
-              ));
-            }
-          }
-          1 => {
-            if dt == 10 {
-              if self.completed_activities == 2 {
-                self.dialog = Some(Dialog::new(
-                  (self.pos.0 - 2, self.pos.1),
-                  "Maybe if I go play that kazoo, I will find a carrot.".to_string(),
-                  true,
-                ));
-              } else {
-                self.dialog = Some(Dialog::new(
-                  (self.pos.0 - 2, self.pos.1),
-                  "Could there be a carrot hidden somewhere else?".to_string(),
-                  true,
-                ));
-              }
-            }
-          }
-          _ => unreachable!(),
-        }
-      }
-      BunnyStage::WalkToKazoo {
-        t: initial_t,
-        init_pos,
-      } => {
-        const TARGET: (i32, i32) = (26, 4);
-        let dt = t - initial_t;
-        if dt > self.dt_to_completion(init_pos, TARGET) {
-          self.stage = BunnyStage::PlayKazoo { t };
-          self.state = BunnyState::Walk1;
-          self.direction = Direction::Left;
-        } else {
-          self.interpolate_pos(t - initial_t, init_pos, TARGET);
-        }
-      }
-      BunnyStage::PlayKazoo { t: initial_t } => {
-        let dt = t - initial_t;
-        if dt == 50 {
-          self.state = BunnyState::HoldKazoo;
-          self.direction = Direction::Left;
-          self.hole.set_kazoo_pos((25, 6));
-        } else if dt == 100 {
-          self.dialog = Some(Dialog::new(
-            (self.pos.0 + 7, self.pos.1),
-            "SHRREEEEEEEEKKKKKK!!!!!!".to_string(),
-            false,
-          ));
-          self.state = BunnyState::BlowKazoo;
-          self.landscape.shreek((25, 6));
-          self.basket.radiate((25, 6));
-          self.hole.radiate((25, 6));
-        } else if dt == 109 {
-          self.carrot.appear();
-        } else if dt == 150 {
-          self.state = BunnyState::Walk1;
-        } else if dt == 265 {
-          self.dialog = None;
-        } else if dt == 275 {
-          self.dialog = Some(Dialog::new(
-            (self.pos.0 + 7, self.pos.1),
-            "Oh, what's that?".to_string(),
-            false,
-          ));
-        }
-      }
-      BunnyStage::WalkToCarrot {
-        t: initial_t,
-        init_pos,
-      } => {
-        const TARGET: (i32, i32) = (31, 13);
-        let dt = t - initial_t;
-        if dt > self.dt_to_completion(init_pos, TARGET) {
-          self.stage = BunnyStage::EatCarrot { t, dialog_idx: 0 };
-          self.state = BunnyState::Walk1;
-          self.direction = Direction::Left;
-        } else {
-          self.interpolate_pos(t - initial_t, init_pos, TARGET);
-        }
-      }
-      BunnyStage::EatCarrot {
-        t: initial_t,
-        dialog_idx,
-      } => {
-        let dt = t - initial_t;
-        match dialog_idx {
-          0 => {
-            if dt == 50 {
-              self.dialog = Some(Dialog::new(
-                (self.pos.0 + 7, self.pos.1),
-                "Is this... a... CARROT!?!?!?!?".to_string(),
-                false,
-              ));
-            }
-          }
-          1 => {
-            if dt == 10 {
-              self.state = BunnyState::HoldKazoo;
-            } else if dt == 40 {
-              self.carrot.make_upside_down();
-            } else if dt == 80 {
-              self.carrot.set_pos((34, 14));
-              self.carrot.delete_head();
-              let letters = self.random_guaranteed_letters();
-              self.carrot.set_target_letters(letters);
-              self.state = BunnyState::Walk1;
-            } else if dt > 85 {
-              if dt % 14 == 0 {
-                self.state = BunnyState::Munch;
-              } else if dt % 14 == 7 {
-                self.state = BunnyState::Walk1;
-                self.carrot.scatter(self.rng);
-              }
-
-              if dt == 200 {
-                self.zoom.zoom((63, 7), 7);
-              } else if dt == 400 {
-                self.zoom.zoom2((25, 6), 5);
-              } else if dt == 600 {
-                self.zoom.disappear();
-              } else if dt == 680 {
-                *self.done.lock().unwrap() = true;
-              }
-            }
-          }
-          _ => unreachable!(),
-        }
+  /// The story can wait on clicks anywhere.
+  fn bounds(&self) -> Option<Bounds> {
+    Some(Bounds::everywhere(Z_IDX))
+  }
+
+  /// Clicks go to whatever was clicked on first, and only reach the bunny and
+  /// the story if nothing took them.
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    let grab_peaches = matches!(self.progress, Progress::Peaches { .. });
+    let mut children = self.children_mut();
+    if !grab_peaches {
+      children.pop();
+    }
+    if let Some(idx) = dispatch(children, (x, y), |entity| entity.click(x, y)) {
+      self.focus = Some(idx);
+      return true;
+    }
+
+    let pos = (x as i32, y as i32);
+    let blinked = self.blink && self.is_on(Target::Bunny, pos);
+    if blinked {
+      self.blink_eyes();
+    }
+    match self.poll_story(StoryEvent::Click(pos)) {
+      Some(next) => {
+        self.run_story(next);
+        true
       }
+      None => blinked,
     }
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    self.landscape.click(x, y);
-    if let BunnyStage::AwaitPeachDestruction {
-      t: _,
-      rem_peaches: _,
-    } = self.stage
-    {
-      self.basket.click(x, y);
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    let consumer = dispatch(self.children_mut(), (x, y), |entity| entity.drag(x, y));
+    if consumer.is_some() {
+      self.focus = consumer;
     }
+    consumer.is_some()
+  }
 
-    let x = x as i32;
-    let y = y as i32;
-    let clicked_bunny =
-      self.pos.0 <= x && x < self.pos.0 + 8 && self.pos.1 <= y && y < self.pos.1 + 4;
-
-    match self.stage {
-      BunnyStage::Sleep1 => {
-        if clicked_bunny {
-          self.stage = BunnyStage::Speak1 {
-            t: self.t,
-            dialog_idx: 0,
-          };
-          self.state = BunnyState::Wake;
-        }
-      }
-      BunnyStage::Speak1 { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t >= t + 50,
-          1 => self.t >= t + 100,
-          2 => false,
-          _ => unreachable!(),
-        } {
-          self.stage = BunnyStage::Speak1 {
-            t: self.t,
-            dialog_idx: dialog_idx + 1,
-          };
-          self.dialog = None;
-        }
-      }
-      BunnyStage::AwaitDecision1 => {
-        if clicked_bunny {
-          self.state = BunnyState::Blink { t: self.t };
-        }
-        if self.basket.contains_click((x, y)) {
-          self.stage = BunnyStage::WalkToBasket {
-            t: self.t,
-            init_pos: self.pos,
-          };
-          self.dialog = None;
-        }
-        if self.hole.contains_click((x, y)) {
-          self.stage = BunnyStage::WalkToHole {
-            t: self.t,
-            init_pos: self.pos,
-          };
-          self.dialog = None;
-        }
-      }
-      BunnyStage::AwaitDecisionBasket => {
-        if clicked_bunny {
-          self.state = BunnyState::Blink { t: self.t };
-        }
-        if self.basket.contains_click((x, y)) {
-          self.stage = BunnyStage::WalkToBasket {
-            t: self.t,
-            init_pos: self.pos,
-          };
-          self.dialog = None;
-        }
-      }
-      BunnyStage::WalkToBasket { t: _, init_pos: _ } => {}
-      BunnyStage::BasketDialog { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t >= t + 50,
-          1 => self.t >= t + 10,
-          2 => self.t >= t + 10,
-          _ => unreachable!(),
-        } {
-          if dialog_idx == 2 {
-            self.stage = BunnyStage::AwaitPeachDestruction {
-              t: self.t,
-              rem_peaches: 4,
-            };
-            self.train_scene.unfreeze();
-          } else {
-            self.stage = BunnyStage::BasketDialog {
-              t: self.t,
-              dialog_idx: dialog_idx + 1,
-            };
-          }
-          self.dialog = None;
-        }
-      }
-      BunnyStage::AwaitPeachDestruction {
-        t: _,
-        rem_peaches: _,
-      } => {
-        if clicked_bunny {
-          self.state = BunnyState::Blink { t: self.t };
-        }
-      }
-      BunnyStage::PeachesHaveNoCarrots { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t > t,
-          1 => self.t >= t + 10,
-          _ => unreachable!(),
-        } {
-          if dialog_idx == 1 {
-            if self.completed_activities == 2 {
-              self.stage = BunnyStage::WalkToKazoo {
-                t: self.t,
-                init_pos: self.pos,
-              };
-            } else {
-              self.stage = BunnyStage::AwaitDecisionHole;
-            }
-          } else {
-            self.stage = BunnyStage::PeachesHaveNoCarrots {
-              t: self.t,
-              dialog_idx: dialog_idx + 1,
-            };
-          }
-          self.dialog = None;
-        }
-      }
-      BunnyStage::AwaitDecisionHole => {
-        if clicked_bunny {
-          self.state = BunnyState::Blink { t: self.t };
-        }
-        if self.hole.contains_click((x, y)) {
-          self.stage = BunnyStage::WalkToHole {
-            t: self.t,
-            init_pos: self.pos,
-          };
-          self.dialog = None;
-        }
-      }
-      BunnyStage::WalkToHole { t: _, init_pos: _ } => {}
-      BunnyStage::HoleDialog { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t >= t + 50,
-          1 => self.t >= t + 10,
-          _ => unreachable!(),
-        } {
-          if dialog_idx == 1 {
-            self.stage = BunnyStage::Dig;
-            let mut letters: Vec<_> = self
-              .random_guaranteed_letters()
-              .into_iter()
-              .map(|letter| (true, letter.0, letter.1))
-              .chain((0..200).map(|_| {
-                let dr = self.rng.gen_range(5.0..50.);
-                let dt = self.rng.gen_range(PI * 0.75..PI * 1.25);
-                let dx = (dt.cos() * dr) as i32;
-                let dy = (dt.sin() * dr) as i32;
-                let letter = self.rng.gen_range('a'..='z');
-                (false, letter, (self.pos.0 + 2 + dx, self.pos.1 + 2 + dy))
-              }))
-              .collect();
-            letters.shuffle(self.rng);
-            self.hole.add_dirt(letters);
-          } else {
-            self.stage = BunnyStage::HoleDialog {
-              t: self.t,
-              dialog_idx: dialog_idx + 1,
-            };
-          }
-          self.dialog = None;
-        }
-      }
-      BunnyStage::Dig => {
-        if self.hole.contains_click((x, y)) && !(0..10).all(|_| self.hole.fling()) {
-          self.stage = BunnyStage::HoleHasNoCarrots {
-            t: self.t,
-            dialog_idx: 0,
-          };
-          self.completed_activities += 1;
-        }
-      }
-      BunnyStage::HoleHasNoCarrots { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t >= t + 50,
-          1 => self.t >= t + 10,
-          _ => unreachable!(),
-        } && (dialog_idx != 0 || !self.hole.contains_click((x, y)))
-        {
-          if dialog_idx == 1 {
-            if self.completed_activities == 2 {
-              self.stage = BunnyStage::WalkToKazoo {
-                t: self.t,
-                init_pos: self.pos,
-              };
-            } else {
-              self.stage = BunnyStage::AwaitDecisionBasket;
-            }
-          } else {
-            self.stage = BunnyStage::HoleHasNoCarrots {
-              t: self.t,
-              dialog_idx: dialog_idx + 1,
-            };
-          }
-          self.dialog = None;
-        }
-      }
-      BunnyStage::WalkToKazoo { t: _, init_pos: _ } => {}
-      BunnyStage::PlayKazoo { t } => {
-        let dt = self.t - t;
-        if dt >= 275 {
-          self.stage = BunnyStage::WalkToCarrot {
-            t: self.t,
-            init_pos: self.pos,
-          };
-          self.dialog = None;
-        }
-      }
-      BunnyStage::WalkToCarrot { t: _, init_pos: _ } => {}
-      BunnyStage::EatCarrot { t, dialog_idx } => {
-        if match dialog_idx {
-          0 => self.t >= t + 50,
-          1 => false,
-          _ => unreachable!(),
-        } {
-          self.stage = BunnyStage::EatCarrot {
-            t: self.t,
-            dialog_idx: dialog_idx + 1,
-          };
-          self.dialog = None;
-        }
+  fn release(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.release(x, y)).is_some()
+  }
+
+  /// Keys go to whatever was last clicked or dragged first, then the other
+  /// children, and otherwise move the story on.
+  fn key(&mut self, key: Key) -> bool {
+    let focus = self.focus;
+    let mut children: Vec<_> = self.children_mut().into_iter().enumerate().collect();
+    children.sort_by_key(|&(idx, _)| Some(idx) != focus);
+    if children.into_iter().any(|(_, entity)| entity.key(key)) {
+      return true;
+    }
+    match self.poll_story(StoryEvent::Key(key)) {
+      Some(next) => {
+        self.run_story(next);
+        true
       }
+      None => false,
     }
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
-    self.landscape.drag(x, y);
-    self.basket.drag(x, y);
+  fn resize(&mut self, width: u32, height: u32) {
+    self.size = (width, height);
+    self.relayout_greeting(width, height);
+    self.landscape.resize(width, height);
+    self.train_scene.resize(width, height);
+    self.zoom.resize(width, height);
+    // Move everything else over to where it goes in the new window.
+    let tuning = self.tuning;
+    self.retune(&tuning);
+    self.pos = (
+      self.pos.0.clamp(0, (width as i32 - BUNNY_SIZE.0).max(0)),
+      self.pos.1.clamp(0, (height as i32 - BUNNY_SIZE.1).max(0)),
+    );
   }
 
-  fn release(&mut self, x: u32, y: u32) {
-    self.landscape.release(x, y);
-    self.basket.release(x, y);
+  fn retune(&mut self, tuning: &Tuning) {
+    self.tuning = *tuning;
+    let tuning = &fit_tuning(tuning, self.size.0, self.size.1);
+    self.landscape.retune(tuning);
+    self.basket.retune(tuning);
+    self.train_scene.retune(tuning);
+    self.hole.retune(tuning);
+    self.carrot.retune(tuning);
+    self.zoom.retune(tuning);
+    if let Some(dialog) = &mut self.dialog {
+      dialog.retune(tuning);
+    }
+    self.step_period = tuning.step_period;
   }
 }
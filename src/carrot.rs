@@ -1,39 +1,63 @@
 use rand::Rng;
-use termion::color;
+use termion::{color, event::Key};
 
 use crate::{
-  entity::Entity,
-  util::{explosion_path, explosion_target_dt, Draw},
+  entity::{Bounds, Entity},
+  force::ForceFields,
+  greeting::Relayout,
+  particle::{Emitter, ParticleSystem},
+  tuning::Tuning,
+  util::Draw,
 };
 
+/// The width and height of the carrot.
+pub const SIZE: (i32, i32) = (1, 3);
 const Z_IDX: i32 = 26;
 const DEBRIS_Z_IDX: i32 = 25;
 
 pub struct Carrot {
   t: usize,
   pos: (i32, i32),
+  /// Where the carrot starts out, until the story moves it.
+  home: (i32, i32),
   appear: Option<usize>,
   upside_down: bool,
   no_head: bool,
   target_letters: Vec<(char, (i32, i32))>,
-  debris: Vec<(usize, char, (i32, i32), bool)>,
+  debris: ParticleSystem,
 }
 
 impl Carrot {
-  pub fn new(pos: (i32, i32)) -> Self {
+  pub fn new(pos: (i32, i32), forces: &ForceFields) -> Self {
     Self {
       t: 0,
       pos,
+      home: pos,
       appear: None,
       upside_down: false,
       no_head: false,
       target_letters: Vec::new(),
-      debris: Vec::new(),
+      // Bites last twice as long as they take to land.
+      debris: ParticleSystem::new(pos, color::AnsiValue::rgb(5, 1, 0), DEBRIS_Z_IDX, forces)
+        .with_lifetime(2),
     }
   }
 
+  /// The top left corner and size of the carrot.
+  pub fn footprint(&self) -> ((i32, i32), (i32, i32)) {
+    (self.pos, SIZE)
+  }
+
+  /// Whether `pos` is on the carrot, once it has appeared.
+  pub fn contains_click(&self, pos: (i32, i32)) -> bool {
+    let dx = pos.0 - self.pos.0;
+    let dy = pos.1 - self.pos.1;
+    self.appear.is_some() && (0..SIZE.0).contains(&dx) && (0..SIZE.1).contains(&dy)
+  }
+
   pub fn set_pos(&mut self, pos: (i32, i32)) {
     self.pos = pos;
+    self.debris.set_origin(pos);
   }
 
   pub fn delete_head(&mut self) {
@@ -52,26 +76,25 @@ impl Carrot {
     self.target_letters = letters;
   }
 
+  /// Moves the letters of the greeting bitten off, or still to be.
+  pub fn move_letters(&mut self, relayout: &Relayout) {
+    for (_, pos) in self.target_letters.iter_mut() {
+      if let Some(to) = relayout.moved(*pos) {
+        *pos = to;
+      }
+    }
+    self.debris.move_letters(relayout);
+  }
+
   pub fn scatter<R: Rng>(&mut self, rng: &mut R) {
-    const RADIUS: i32 = 15;
-    self.debris.append(
-      &mut (0..15)
-        .map(|_| {
-          let mut dx = rng.gen_range(-RADIUS..=RADIUS);
-          let mut dy = rng.gen_range(-RADIUS..=RADIUS);
-          while dx * dx + dy * dy > (RADIUS * RADIUS) || self.pos.0 + dx < 0 || self.pos.1 + dy < 0
-          {
-            dx = rng.gen_range(-RADIUS..=RADIUS);
-            dy = rng.gen_range(-RADIUS..=RADIUS);
-          }
-
-          let letter = rng.gen_range('a'..='z');
-          (self.t, letter, (self.pos.0 + dx, self.pos.1 + dy), false)
-        })
-        .collect(),
-    );
+    const EMITTER: Emitter = Emitter {
+      count: 15,
+      radius: 15,
+      glyphs: 'a'..='z',
+    };
+    self.debris.burst(&EMITTER, rng);
     if let Some((c, pos)) = self.target_letters.pop() {
-      self.debris.push((self.t, c, pos, true));
+      self.debris.emit(c, pos, true);
     }
   }
 }
@@ -127,30 +150,38 @@ impl Entity for Carrot {
         Some(initial_t) => self.t - initial_t,
         None => 0,
       })
-      .chain(self.debris.iter().map(move |(t, c, (x, y), targeted)| {
-        (
-          Draw::new(*c)
-            .with_fg(color::AnsiValue::rgb(5, 1, 0))
-            .with_z(DEBRIS_Z_IDX + if *targeted { 1 } else { 0 }),
-          explosion_path((self.t - t) as f32, (*x, *y), self.pos),
-        )
-      })),
+      .chain(self.debris.iterate_tiles()),
     )
   }
 
   fn tick(&mut self, t: usize) {
     self.t = t;
-    self.debris = self
-      .debris
-      .clone()
-      .into_iter()
-      .filter(|(t, _, pos, targeted)| {
-        *targeted || 2 * explosion_target_dt(*pos, self.pos) > self.t - t
-      })
-      .collect();
+    self.debris.tick(t);
   }
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, tuning: &Tuning) {
+    if self.pos == self.home {
+      self.set_pos(tuning.carrot);
+    }
+    self.home = tuning.carrot;
+  }
 }
@@ -0,0 +1,69 @@
+use std::{
+  fmt::Write as _,
+  io::{self, Write},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Writes terminal output as an asciinema asciicast v2 recording: a JSON
+/// header line, followed by one JSON array per event.
+pub struct Asciicast<W: Write> {
+  out: W,
+}
+
+impl<W: Write> Asciicast<W> {
+  pub fn new(mut out: W, width: u32, height: u32) -> io::Result<Self> {
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |elapsed| elapsed.as_secs());
+    writeln!(
+      out,
+      r#"{{"version": 2, "width": {width}, "height": {height}, "timestamp": {timestamp}}}"#
+    )?;
+    Ok(Self { out })
+  }
+
+  /// Records `data` as having been written to the terminal `time` after the
+  /// recording started.
+  pub fn output(&mut self, time: Duration, data: &[u8]) -> io::Result<()> {
+    if data.is_empty() {
+      return Ok(());
+    }
+    writeln!(
+      self.out,
+      r#"[{:.6}, "o", "{}"]"#,
+      time.as_secs_f64(),
+      json_escape(&String::from_utf8_lossy(data))
+    )
+  }
+
+  /// Records the terminal being resized.
+  pub fn resize(&mut self, time: Duration, width: u32, height: u32) -> io::Result<()> {
+    writeln!(
+      self.out,
+      r#"[{:.6}, "r", "{width}x{height}"]"#,
+      time.as_secs_f64()
+    )
+  }
+
+  pub fn finish(&mut self) -> io::Result<()> {
+    self.out.flush()
+  }
+}
+
+fn json_escape(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '"' => escaped.push_str("\\\""),
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      '\t' => escaped.push_str("\\t"),
+      c if (c as u32) < 0x20 || c == '\x7f' => {
+        write!(escaped, "\\u{:04x}", c as u32).unwrap();
+      }
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
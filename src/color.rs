@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use termion::color::{self, AnsiValue};
+
+/// The xterm values of the 16 system colors.
+const SYSTEM_COLORS: [Rgb; 16] = [
+  Rgb(0, 0, 0),
+  Rgb(205, 0, 0),
+  Rgb(0, 205, 0),
+  Rgb(205, 205, 0),
+  Rgb(0, 0, 238),
+  Rgb(205, 0, 205),
+  Rgb(0, 205, 205),
+  Rgb(229, 229, 229),
+  Rgb(127, 127, 127),
+  Rgb(255, 0, 0),
+  Rgb(0, 255, 0),
+  Rgb(255, 255, 0),
+  Rgb(92, 92, 255),
+  Rgb(255, 0, 255),
+  Rgb(0, 255, 255),
+  Rgb(255, 255, 255),
+];
+
+/// Channel intensities of the 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can display.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+  TrueColor,
+  Ansi256,
+  Ansi16,
+}
+
+impl ColorMode {
+  /// Guesses the color support of the terminal from `COLORTERM` and `TERM`.
+  pub fn detect() -> Self {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    Self::from_env(&var("COLORTERM"), &var("TERM"))
+  }
+
+  /// The color support implied by values of `COLORTERM` and `TERM`, which
+  /// are empty if unset.
+  fn from_env(colorterm: &str, term: &str) -> Self {
+    if colorterm == "truecolor" || colorterm == "24bit" {
+      ColorMode::TrueColor
+    } else if matches!(term, "linux" | "vt100" | "vt220" | "ansi")
+      || term.ends_with("-16color")
+      || term.ends_with("-8color")
+    {
+      ColorMode::Ansi16
+    } else {
+      ColorMode::Ansi256
+    }
+  }
+}
+
+/// A 24-bit color, downsampled to whatever the terminal supports when drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+  fn dist2(&self, other: &Rgb) -> u32 {
+    let dr = self.0 as i32 - other.0 as i32;
+    let dg = self.1 as i32 - other.1 as i32;
+    let db = self.2 as i32 - other.2 as i32;
+    (2 * dr * dr + 4 * dg * dg + 3 * db * db) as u32
+  }
+
+  /// The nearest color in the 256-color palette, ignoring the system colors
+  /// since their values vary between terminals.
+  pub fn to_ansi256(self) -> AnsiValue {
+    let cube_idx = |c: u8| {
+      (0..CUBE_LEVELS.len())
+        .min_by_key(|&idx| (CUBE_LEVELS[idx] as i32 - c as i32).abs())
+        .unwrap() as u8
+    };
+    let (r, g, b) = (cube_idx(self.0), cube_idx(self.1), cube_idx(self.2));
+    let cube = AnsiValue::rgb(r, g, b);
+
+    let avg = (self.0 as u32 + self.1 as u32 + self.2 as u32) / 3;
+    let gray = AnsiValue::grayscale((avg.saturating_sub(3) / 10).min(23) as u8);
+
+    if self.dist2(&Rgb::from(gray)) < self.dist2(&Rgb::from(cube)) {
+      gray
+    } else {
+      cube
+    }
+  }
+
+  /// The index of the nearest of the 16 system colors.
+  pub fn to_ansi16(self) -> u8 {
+    (0..SYSTEM_COLORS.len())
+      .min_by_key(|&idx| self.dist2(&SYSTEM_COLORS[idx]))
+      .unwrap() as u8
+  }
+
+  pub fn fg_string(&self, mode: ColorMode) -> String {
+    match mode {
+      ColorMode::TrueColor => color::Rgb(self.0, self.1, self.2).fg_string(),
+      ColorMode::Ansi256 => self.to_ansi256().fg_string(),
+      ColorMode::Ansi16 => match self.to_ansi16() {
+        idx @ 0..=7 => format!("\x1b[{}m", 30 + idx),
+        idx => format!("\x1b[{}m", 90 + idx - 8),
+      },
+    }
+  }
+
+  pub fn bg_string(&self, mode: ColorMode) -> String {
+    match mode {
+      ColorMode::TrueColor => color::Rgb(self.0, self.1, self.2).bg_string(),
+      ColorMode::Ansi256 => self.to_ansi256().bg_string(),
+      ColorMode::Ansi16 => match self.to_ansi16() {
+        idx @ 0..=7 => format!("\x1b[{}m", 40 + idx),
+        idx => format!("\x1b[{}m", 100 + idx - 8),
+      },
+    }
+  }
+}
+
+impl From<AnsiValue> for Rgb {
+  fn from(AnsiValue(value): AnsiValue) -> Self {
+    match value {
+      0..=15 => SYSTEM_COLORS[value as usize],
+      16..=231 => {
+        let idx = value - 16;
+        Rgb(
+          CUBE_LEVELS[(idx / 36) as usize],
+          CUBE_LEVELS[(idx / 6 % 6) as usize],
+          CUBE_LEVELS[(idx % 6) as usize],
+        )
+      }
+      232..=255 => {
+        let level = 8 + 10 * (value - 232);
+        Rgb(level, level, level)
+      }
+    }
+  }
+}
+
+impl FromStr for Rgb {
+  type Err = String;
+
+  /// Parses a color written as `#rrggbb`.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let hex = s
+      .strip_prefix('#')
+      .filter(|hex| hex.len() == 6 && hex.is_ascii())
+      .ok_or_else(|| format!("Expected a color like #rrggbb, got '{s}'"))?;
+    let channel = |idx: usize| {
+      u8::from_str_radix(&hex[idx..idx + 2], 16)
+        .map_err(|_| format!("Expected a color like #rrggbb, got '{s}'"))
+    };
+    Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn palette_colors_round_trip() {
+    // The cube and the grayscale ramp, but not the system colors.
+    for value in 16..=255 {
+      assert_eq!(Rgb::from(AnsiValue(value)).to_ansi256().0, value);
+    }
+  }
+
+  #[test]
+  fn grays_use_the_grayscale_ramp() {
+    for level in [20, 50, 100, 128, 200, 240] {
+      let value = Rgb(level, level, level).to_ansi256().0;
+      assert!((232..=255).contains(&value), "{level} became {value}");
+    }
+  }
+
+  #[test]
+  fn primaries_use_the_matching_system_color() {
+    assert_eq!(Rgb(0, 0, 0).to_ansi16(), 0);
+    assert_eq!(Rgb(200, 0, 0).to_ansi16(), 1);
+    assert_eq!(Rgb(0, 0, 255).to_ansi16(), 4);
+    assert_eq!(Rgb(255, 0, 0).to_ansi16(), 9);
+    assert_eq!(Rgb(0, 255, 0).to_ansi16(), 10);
+    assert_eq!(Rgb(255, 255, 255).to_ansi16(), 15);
+  }
+
+  #[test]
+  fn detects_color_mode_from_env() {
+    assert_eq!(
+      ColorMode::from_env("truecolor", "xterm"),
+      ColorMode::TrueColor
+    );
+    assert_eq!(ColorMode::from_env("24bit", ""), ColorMode::TrueColor);
+    assert_eq!(
+      ColorMode::from_env("", "xterm-256color"),
+      ColorMode::Ansi256
+    );
+    assert_eq!(ColorMode::from_env("", "xterm"), ColorMode::Ansi256);
+    assert_eq!(ColorMode::from_env("", "xterm-16color"), ColorMode::Ansi16);
+    assert_eq!(ColorMode::from_env("", "linux"), ColorMode::Ansi16);
+  }
+}
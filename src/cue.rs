@@ -0,0 +1,74 @@
+/// Identifies a scheduled cue, so it can be cancelled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CueId(usize);
+
+/// Actions scheduled to fire once, on the first tick at or after their time.
+/// Cues whose time has already passed fire on the next poll, so skipped ticks
+/// delay a cue instead of losing it.
+pub struct Cues<A> {
+  /// Pending cues, sorted by time. Cues for the same time keep the order they
+  /// were scheduled in.
+  cues: Vec<(usize, CueId, A)>,
+  next_id: usize,
+}
+
+impl<A> Cues<A> {
+  pub fn new() -> Self {
+    Self {
+      cues: Vec::new(),
+      next_id: 0,
+    }
+  }
+
+  /// Schedules `action` for time `t`.
+  pub fn at(&mut self, t: usize, action: A) -> CueId {
+    let id = CueId(self.next_id);
+    self.next_id += 1;
+    let idx = self.cues.partition_point(|&(cue_t, _, _)| cue_t <= t);
+    self.cues.insert(idx, (t, id, action));
+    id
+  }
+
+  /// Unschedules the cue `id`, returning its action if it hadn't fired yet.
+  pub fn cancel(&mut self, id: CueId) -> Option<A> {
+    let idx = self.cues.iter().position(|&(_, cue_id, _)| cue_id == id)?;
+    Some(self.cues.remove(idx).2)
+  }
+
+  /// Removes and returns the earliest cue due at or before `t`. Actions may
+  /// schedule more cues, which are returned by later calls if they are
+  /// already due.
+  pub fn pop_due(&mut self, t: usize) -> Option<A> {
+    match self.cues.first() {
+      Some(&(cue_t, _, _)) if cue_t <= t => Some(self.cues.remove(0).2),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cancelled_cues_never_fire() {
+    let mut cues = Cues::new();
+    cues.at(5, 'a');
+    let b = cues.at(5, 'b');
+    cues.at(7, 'c');
+
+    assert_eq!(cues.cancel(b), Some('b'));
+    assert_eq!(cues.cancel(b), None);
+    assert_eq!(cues.pop_due(10), Some('a'));
+    assert_eq!(cues.pop_due(10), Some('c'));
+    assert_eq!(cues.pop_due(10), None);
+  }
+
+  #[test]
+  fn fired_cues_cant_be_cancelled() {
+    let mut cues = Cues::new();
+    let a = cues.at(3, 'a');
+    assert_eq!(cues.pop_due(3), Some('a'));
+    assert_eq!(cues.cancel(a), None);
+  }
+}
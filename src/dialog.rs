@@ -1,6 +1,12 @@
 use std::iter;
 
-use crate::{entity::Entity, util::Draw};
+use termion::event::Key;
+
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  util::{Attrs, Draw},
+};
 
 const MAX_LINE_LEN: usize = 40;
 const Z_IDX: i32 = 10;
@@ -9,11 +15,22 @@ pub struct Dialog {
   src: (i32, i32),
   text: String,
   to_left: bool,
+  attrs: Attrs,
 }
 
 impl Dialog {
   pub fn new(src: (i32, i32), text: String, to_left: bool) -> Self {
-    Self { src, text, to_left }
+    Self {
+      src,
+      text,
+      to_left,
+      attrs: Attrs::default(),
+    }
+  }
+
+  /// Sets the attributes of the text inside the box.
+  pub fn with_attrs(self, attrs: Attrs) -> Self {
+    Self { attrs, ..self }
   }
 
   fn to_lines(&self) -> Vec<String> {
@@ -157,7 +174,7 @@ impl Entity for Dialog {
           .enumerate()
           .map(move |(col, c)| {
             (
-              Draw::new(c).with_z(Z_IDX),
+              Draw::new(c).with_attrs(self.attrs).with_z(Z_IDX),
               (x + col as i32 + 3, y - num_lines - 1 + row as i32),
             )
           })
@@ -167,7 +184,24 @@ impl Entity for Dialog {
 
   fn tick(&mut self, _t: usize) {}
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, _tuning: &Tuning) {}
 }
@@ -1,15 +1,30 @@
 use std::io::Write;
 
-use crate::{util::Draw, window::Window};
+use termion::event::Key;
+
+use crate::{tuning::Tuning, util::Draw, window::Window};
 
 pub trait Entity {
   fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_>;
 
   fn tick(&mut self, t: usize);
 
-  fn click(&mut self, x: u32, y: u32);
-  fn drag(&mut self, x: u32, y: u32);
-  fn release(&mut self, x: u32, y: u32);
+  /// Where the entity takes mouse events, or `None` if it takes none.
+  fn bounds(&self) -> Option<Bounds>;
+
+  /// Mouse events, which return whether the entity consumed the event, in
+  /// which case nothing underneath it sees it.
+  fn click(&mut self, x: u32, y: u32) -> bool;
+  fn drag(&mut self, x: u32, y: u32) -> bool;
+  fn release(&mut self, x: u32, y: u32) -> bool;
+  /// Called when a key is pressed, returning whether the entity consumed it.
+  fn key(&mut self, key: Key) -> bool;
+
+  /// Called when the window has been resized, before the next tick.
+  fn resize(&mut self, width: u32, height: u32);
+
+  /// Called when the tuning has changed, before the next tick.
+  fn retune(&mut self, tuning: &Tuning);
 
   fn render<W: Write>(&self, window: &mut Window<W>)
   where
@@ -23,3 +38,69 @@ pub trait Entity {
     })
   }
 }
+
+/// The area of the window an entity takes mouse events in, and how far up it
+/// is, so the topmost of overlapping entities sees events first.
+#[derive(Clone, Copy)]
+pub struct Bounds {
+  /// The top left corner and size, or `None` for the whole window.
+  rect: Option<((i32, i32), (i32, i32))>,
+  z_idx: i32,
+}
+
+impl Bounds {
+  pub fn rect(pos: (i32, i32), size: (i32, i32), z_idx: i32) -> Self {
+    Self {
+      rect: Some((pos, size)),
+      z_idx,
+    }
+  }
+
+  /// For entities that take events anywhere, like something following the
+  /// mouse.
+  pub fn everywhere(z_idx: i32) -> Self {
+    Self { rect: None, z_idx }
+  }
+
+  /// Bounds for a container of entities with `children` bounds, which takes
+  /// events anywhere at the z-index of its topmost child and leaves
+  /// hit-testing to its children, or takes none if none of them do.
+  pub fn enclosing(children: impl IntoIterator<Item = Option<Bounds>>) -> Option<Self> {
+    children
+      .into_iter()
+      .flatten()
+      .map(|bounds| bounds.z_idx)
+      .max()
+      .map(Self::everywhere)
+  }
+
+  pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+    self.rect.is_none_or(|(pos, size)| {
+      (pos.0..pos.0 + size.0).contains(&x) && (pos.1..pos.1 + size.1).contains(&y)
+    })
+  }
+}
+
+/// Offers a mouse event at `(x, y)` to each of `entities` whose bounds hold
+/// it, topmost first, until one consumes it. Entities at the same z-index are
+/// offered it in order. Returns the index of the one that consumed it, if any.
+pub fn dispatch<'a>(
+  entities: impl IntoIterator<Item = &'a mut dyn Entity>,
+  (x, y): (u32, u32),
+  mut handle: impl FnMut(&mut dyn Entity) -> bool,
+) -> Option<usize> {
+  let mut hits: Vec<_> = entities
+    .into_iter()
+    .enumerate()
+    .filter_map(|(idx, entity)| {
+      let bounds = entity.bounds()?;
+      bounds
+        .contains((x as i32, y as i32))
+        .then_some((bounds.z_idx, idx, entity))
+    })
+    .collect();
+  hits.sort_by_key(|&(z_idx, _, _)| std::cmp::Reverse(z_idx));
+  hits
+    .into_iter()
+    .find_map(|(_, idx, entity)| handle(entity).then_some(idx))
+}
@@ -0,0 +1,171 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::tween::{Easing, Lerp};
+
+/// Rows are about 11/5 times as tall as columns are wide, so distances down
+/// are scaled by this to make fields round on screen.
+const ROW_HEIGHT: f32 = 11.;
+const COLUMN_WIDTH: f32 = 5.;
+
+/// Something that moves things around over time. Each acts for `ticks`, after
+/// which whatever it moved stays where it was left.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Force {
+  /// A ring spreading out from `center` to `radius`, pushing everything it
+  /// passes out to its edge.
+  Shockwave {
+    center: (i32, i32),
+    radius: i32,
+    ticks: usize,
+    easing: Easing,
+  },
+  /// Pushes everything within `radius` of `center` out by up to `distance`,
+  /// less the further out it is. A negative distance pulls things in.
+  Radial {
+    center: (i32, i32),
+    radius: i32,
+    distance: i32,
+    ticks: usize,
+  },
+  /// Swirls everything within `radius` of `center` around it by up to
+  /// `degrees` clockwise, less the further out it is.
+  Vortex {
+    center: (i32, i32),
+    radius: i32,
+    degrees: i32,
+    ticks: usize,
+  },
+  /// Blows everything over by `offset`.
+  Wind { offset: (i32, i32), ticks: usize },
+}
+
+impl Force {
+  /// Where something at `pos` has been moved to `dt` ticks after the force
+  /// started.
+  fn displace(&self, dt: usize, pos: (i32, i32)) -> (i32, i32) {
+    match *self {
+      Force::Shockwave {
+        center,
+        radius,
+        ticks,
+        easing,
+      } => {
+        let ring = (radius as f32 * easing.apply(progress(dt, ticks))).round();
+        let (dx, dy) = offset(center, pos);
+        let d = dx.powi(2) + dy.powi(2);
+        if d < ring * ring {
+          let scale = ring / d.sqrt();
+          place(center, (dx * scale, dy * scale))
+        } else {
+          pos
+        }
+      }
+      Force::Radial {
+        center,
+        radius,
+        distance,
+        ticks,
+      } => {
+        let (dx, dy) = offset(center, pos);
+        let d = dx.hypot(dy);
+        if d == 0. || d >= radius as f32 {
+          return pos;
+        }
+        let push = distance as f32 * (1. - d / radius as f32) * progress(dt, ticks);
+        let scale = (d + push).max(0.) / d;
+        place(center, (dx * scale, dy * scale))
+      }
+      Force::Vortex {
+        center,
+        radius,
+        degrees,
+        ticks,
+      } => {
+        let (dx, dy) = offset(center, pos);
+        let d = dx.hypot(dy);
+        if d >= radius as f32 {
+          return pos;
+        }
+        let angle = (degrees as f32).to_radians() * (1. - d / radius as f32) * progress(dt, ticks);
+        let (sin, cos) = angle.sin_cos();
+        place(center, (dx * cos - dy * sin, dx * sin + dy * cos))
+      }
+      Force::Wind { offset, ticks } => {
+        let (dx, dy) = (0, 0).lerp(offset, progress(dt, ticks));
+        (pos.0 + dx, pos.1 + dy)
+      }
+    }
+  }
+}
+
+/// The fraction of its `ticks` a force has been acting for.
+fn progress(dt: usize, ticks: usize) -> f32 {
+  if ticks == 0 {
+    1.
+  } else {
+    dt.min(ticks) as f32 / ticks as f32
+  }
+}
+
+/// How far `pos` is from `center`, with rows scaled to match columns.
+fn offset(center: (i32, i32), pos: (i32, i32)) -> (f32, f32) {
+  (
+    (pos.0 - center.0) as f32,
+    (pos.1 - center.1) as f32 * ROW_HEIGHT / COLUMN_WIDTH,
+  )
+}
+
+/// The inverse of `offset`.
+fn place(center: (i32, i32), (dx, dy): (f32, f32)) -> (i32, i32) {
+  (
+    center.0 + dx as i32,
+    center.1 + (dy * COLUMN_WIDTH / ROW_HEIGHT) as i32,
+  )
+}
+
+/// The forces acting on the scene. Clones share the same fields, so a field
+/// added through one acts on everything holding any of them.
+#[derive(Clone, Default)]
+pub struct ForceFields {
+  /// When each field started, in the order they were added.
+  fields: Rc<RefCell<Vec<(usize, Force)>>>,
+}
+
+impl ForceFields {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Starts `force` acting at time `t`, alongside any already acting.
+  pub fn add(&self, t: usize, force: Force) {
+    self.fields.borrow_mut().push((t, force));
+  }
+
+  /// How far the fields that started since time `since` move something at
+  /// `pos` over the tick up to `t`, for things that move around on their own
+  /// between ticks.
+  pub fn nudge(&self, since: usize, t: usize, pos: (i32, i32)) -> (i32, i32) {
+    self
+      .fields
+      .borrow()
+      .iter()
+      .filter(|&&(start, _)| start >= since && start < t)
+      .fold((0, 0), |(dx, dy), (start, force)| {
+        let before = force.displace(t - 1 - start, pos);
+        let after = force.displace(t - start, pos);
+        (dx + after.0 - before.0, dy + after.1 - before.1)
+      })
+  }
+
+  /// Where something that has been at `pos` since time `since` is by time
+  /// `t`. Only fields that started since then move it, each acting on where
+  /// the ones before it left it.
+  pub fn displace(&self, since: usize, t: usize, pos: (i32, i32)) -> (i32, i32) {
+    self
+      .fields
+      .borrow()
+      .iter()
+      .filter(|&&(start, _)| start >= since && start <= t)
+      .fold(pos, |pos, (start, force)| force.displace(t - start, pos))
+  }
+}
@@ -0,0 +1,301 @@
+//! Golden-frame tests. The story is run headlessly with a fixed seed, and
+//! frames are compared against the text fixtures in `tests/golden`.
+//!
+//! After an intended visual change, rerun with `UPDATE_GOLDEN=1` to rewrite
+//! the fixtures, and check the diff.
+
+use std::{fs, path::PathBuf};
+
+use crate::{
+  args::Args,
+  asset::Assets,
+  color::Rgb,
+  replay::Replay,
+  story::{Story, DEFAULT_STORY},
+  window::Window,
+};
+
+const HEADER: &str = "seed 27418995609531717\nsize 120 40\n";
+
+/// Plays back `replay` until it quits or the story finishes, calling `check`
+/// with the window after each tick in `snapshots`. Returns whether the story
+/// finished.
+fn run<F>(replay: &str, snapshots: &[usize], check: F) -> bool
+where
+  F: FnMut(usize, &Window<std::io::Sink>),
+{
+  run_with_args(&Args::default(), replay, snapshots, check)
+}
+
+/// `run`, with options like the greeting taken from `args`.
+fn run_with_args<F>(args: &Args, replay: &str, snapshots: &[usize], mut check: F) -> bool
+where
+  F: FnMut(usize, &Window<std::io::Sink>),
+{
+  let replay = Replay::parse(replay).unwrap();
+  let (width, height) = replay.size();
+  let summary = crate::run(
+    Window::headless(width, height),
+    None,
+    Some(replay),
+    Story::parse(DEFAULT_STORY).unwrap(),
+    &Assets::builtin(),
+    args,
+    |t, window| {
+      if snapshots.contains(&t) {
+        check(t, window);
+      }
+    },
+  );
+  summary.finished
+}
+
+fn fixture_path(dir: &str, name: &str) -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("tests")
+    .join(dir)
+    .join(name)
+}
+
+fn assert_golden(name: &str, frame: &str) {
+  let path = fixture_path("golden", &format!("{name}.txt"));
+  if std::env::var_os("UPDATE_GOLDEN").is_some() {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, frame).unwrap();
+    return;
+  }
+
+  let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+    panic!(
+      "Missing fixture {}, run with UPDATE_GOLDEN=1",
+      path.display()
+    )
+  });
+  if expected != frame {
+    let (line, (want, got)) = expected
+      .lines()
+      .zip(frame.lines())
+      .enumerate()
+      .find(|(_, (want, got))| want != got)
+      .unwrap_or((expected.lines().count(), ("<eof>", "<eof>")));
+    panic!(
+      "Frame differs from {} at line {}:\nexpected: {want}\n     got: {got}",
+      path.display(),
+      line + 1
+    );
+  }
+}
+
+/// Ticks through the opening dialog, up to where the bunny asks for help.
+const OPENING: &str = "\
+1 press left 53 12
+60 press left 1 1
+170 press left 1 1
+";
+
+#[test]
+fn sleeping() {
+  let replay = format!("{HEADER}1 quit\n");
+  run(&replay, &[0], |_, window| {
+    assert_golden("sleeping", &window.to_text());
+  });
+}
+
+#[test]
+fn bunny_is_drawn_over_grass() {
+  let replay = format!("{HEADER}1 quit\n");
+  run(&replay, &[0], |_, window| {
+    let ear = window.cell(50, 10).unwrap();
+    assert_eq!(ear.item(), '(');
+    assert_eq!(ear.fg(), Some(Rgb(228, 228, 228)));
+  });
+}
+
+#[test]
+fn tiny_window() {
+  // Too short for the train to sit on the track.
+  let replay = "seed 27418995609531717\nsize 10 3\n1 press left 5 2\n100 quit\n";
+  run(replay, &[1, 99], |t, window| {
+    let text = window.to_text();
+    assert!(text.contains("(@"), "No train in the window");
+    assert_golden(&format!("tiny_window_t{t}"), &text);
+  });
+}
+
+#[test]
+fn shrink_to_tiny_window() {
+  let replay = format!(
+    "{HEADER}1 press left 53 12\n20 resize 10 3\n40 resize 1 1\n60 resize 120 40\n100 quit\n"
+  );
+  run(&replay, &[21, 41, 61], |t, window| {
+    let text = window.to_text();
+    let rows: Vec<_> = text.lines().collect();
+    match t {
+      21 => assert!(text.contains("(@"), "No train in the window"),
+      // The bunny's ear, drawn over the train in the only cell.
+      41 => assert_eq!(rows, ["("]),
+      // Back on the track, five eighths of the way down.
+      _ => assert!(rows[25].starts_with("=+===+"), "No track on row 25"),
+    }
+    assert_golden(&format!("shrink_to_tiny_window_t{t}"), &text);
+  });
+}
+
+#[test]
+fn wake_up() {
+  let replay = format!("{HEADER}1 press left 53 12\n61 quit\n");
+  run(&replay, &[60], |_, window| {
+    assert_golden("wake_up", &window.to_text());
+  });
+}
+
+#[test]
+fn walk_to_hole() {
+  let replay = format!("{HEADER}{OPENING}185 press left 104 14\n301 quit\n");
+  run(&replay, &[180, 300], |t, window| {
+    assert_golden(&format!("walk_to_hole_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn dig_hole_in_small_window() {
+  // Everything is moved into the window, so the hole can be reached and dug.
+  let clicks: String = (730..760)
+    .map(|t| format!("{t} press left 70 9\n"))
+    .collect();
+  let replay = format!(
+    "seed 27418995609531717\nsize 80 24\n\
+1 key space\n60 key enter\n170 key space\n185 key 2\n700 key space\n720 key space\n\
+{clicks}851 quit\n"
+  );
+  run(&replay, &[600, 850], |t, window| {
+    assert_golden(&format!("dig_hole_in_small_window_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn walk_to_moved_hole() {
+  // The bunny walks up to the hole wherever it is tuned to be.
+  let replay = format!("{HEADER}0 tune hole = 70 16\n{OPENING}185 key 2\n401 quit\n");
+  run(&replay, &[400], |t, window| {
+    assert_golden(&format!("walk_to_moved_hole_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn walk_to_unreachable_spot() {
+  // With the hole against the left edge, the spot beside it is off screen, so
+  // the bunny stays where it is.
+  let replay = format!("{HEADER}0 tune hole = 2 28\n{OPENING}185 key 2\n301 quit\n");
+  run(&replay, &[180, 300], |t, window| {
+    assert_golden(&format!("walk_to_unreachable_spot_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn throw_peach_back_to_start() {
+  // Drag a peach around and back to where it was a moment ago, which drops
+  // it where it is.
+  let full_story = fs::read_to_string(fixture_path("replays", "full_story.replay")).unwrap();
+  let (to_peaches, _) = full_story.split_once("# Put every peach").unwrap();
+  let replay = format!(
+    "{to_peaches}\
+1750 press left 13 13
+1751 hold 16 14
+1752 hold 20 15
+1753 hold 16 14
+1754 release 16 14
+1761 quit
+"
+  );
+  run(&replay, &[1760], |t, window| {
+    assert_golden(
+      &format!("throw_peach_back_to_start_t{t}"),
+      &window.to_text(),
+    );
+  });
+}
+
+#[test]
+fn walk_to_hole_by_keyboard() {
+  // The same as clicking through the opening and picking the hole.
+  let replay = format!("{HEADER}1 key space\n60 key enter\n170 key space\n185 key 2\n301 quit\n");
+  run(&replay, &[300], |t, window| {
+    assert_golden(&format!("walk_to_hole_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn pause() {
+  // Nothing moves for the 50 ticks spent paused, and clicks meanwhile are
+  // ignored, so the hole has to be picked again after.
+  let replay = format!(
+    "{HEADER}{OPENING}181 key esc\n185 press left 104 14\n231 key esc\n235 press left 104 14\n351 quit\n"
+  );
+  run(&replay, &[230, 350], |t, window| {
+    let golden = if t == 230 { 180 } else { 300 };
+    assert_golden(&format!("walk_to_hole_t{golden}"), &window.to_text());
+  });
+}
+
+#[test]
+fn full_story() {
+  let replay = fs::read_to_string(fixture_path("replays", "full_story.replay")).unwrap();
+  let finished = run(&replay, &[800, 1990, 2500, 3300], |t, window| {
+    assert_golden(&format!("full_story_t{t}"), &window.to_text());
+  });
+  assert!(finished, "The story should run to the end");
+}
+
+#[test]
+fn zoom_on_long_message() {
+  // The closing zoom is centered on the greeting, and wide enough for all of
+  // it.
+  let args = Args {
+    message: Some("Congratulations on the big promotion Alexandra".to_string()),
+    ..Args::default()
+  };
+  let replay = fs::read_to_string(fixture_path("replays", "full_story.replay")).unwrap();
+  let finished = run_with_args(&args, &replay, &[3300], |t, window| {
+    assert_golden(&format!("zoom_on_long_message_t{t}"), &window.to_text());
+  });
+  assert!(finished, "The story should run to the end");
+}
+
+#[test]
+fn resize_after_digging() {
+  // The letters dug out of the hole move along with the rest of the greeting.
+  let full_story = fs::read_to_string(fixture_path("replays", "full_story.replay")).unwrap();
+  let (to_basket, _) = full_story.split_once("# Then the basket.").unwrap();
+  let replay = format!("{to_basket}885 resize 90 30\n886 quit\n");
+  run(&replay, &[884, 885], |t, window| {
+    assert_golden(&format!("resize_after_digging_t{t}"), &window.to_text());
+  });
+}
+
+#[test]
+fn throw_peaches() {
+  // Play the full story up to the peaches, then throw one onto the grass and
+  // one into the water, which goes back to the basket. The held peach takes
+  // the drags, so the water doesn't ripple under it on the way.
+  let full_story = fs::read_to_string(fixture_path("replays", "full_story.replay")).unwrap();
+  let (to_peaches, _) = full_story.split_once("# Put every peach").unwrap();
+  let replay = format!(
+    "{to_peaches}\
+1750 press left 13 13
+1751 hold 16 14
+1752 hold 20 15
+1753 hold 24 16
+1754 release 24 16
+1755 press left 16 13
+1756 hold 20 20
+1757 hold 24 28
+1758 hold 28 36
+1758 release 28 36
+1791 quit
+"
+  );
+  run(&replay, &[1762, 1790], |t, window| {
+    assert_golden(&format!("throw_peaches_t{t}"), &window.to_text());
+  });
+}
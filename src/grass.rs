@@ -1,7 +1,11 @@
-use rand::Rng;
-use termion::color;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use termion::{color, event::Key};
 
-use crate::{entity::Entity, util::Draw};
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  util::Draw,
+};
 
 const Z_IDX: i32 = 2;
 
@@ -16,18 +20,25 @@ pub struct Grass {
   height: u32,
   grid: Vec<Draw>,
   shreek: Option<Shreek>,
+  /// How long a shreek lasts.
+  shreek_ticks: usize,
+  // Used to grow the field when the window is resized.
+  rng: StdRng,
 }
 
 impl Grass {
-  pub fn new<R: Rng>(width: u32, height: u32, rand: &mut R) -> Self {
+  pub fn new<R: Rng>(width: u32, height: u32, tuning: &Tuning, rand: &mut R) -> Self {
+    let grid = (0..(width * height))
+      .map(|_| Self::rand_tile(rand))
+      .collect();
     Self {
       t: 0,
       width,
       height,
-      grid: (0..(width * height))
-        .map(|_| Self::rand_tile(rand))
-        .collect(),
+      grid,
       shreek: None,
+      shreek_ticks: tuning.shreek_ticks,
+      rng: StdRng::seed_from_u64(rand.gen()),
     }
   }
 
@@ -102,13 +113,49 @@ impl Entity for Grass {
   fn tick(&mut self, t: usize) {
     self.t = t;
     if let Some(Shreek { t, pos: _ }) = self.shreek {
-      if self.t - t > 125 {
+      if self.t - t > self.shreek_ticks {
         self.shreek = None;
       }
     }
   }
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  /// Keeps every surviving tile where it was, and grows fresh grass into any
+  /// newly exposed or previously deleted cells.
+  fn resize(&mut self, width: u32, height: u32) {
+    let mut grid = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+      for x in 0..width {
+        let tile = if x < self.width && y < self.height && self.has_tile(x, y) {
+          self.grid[self.idx(x, y)].clone()
+        } else {
+          Self::rand_tile(&mut self.rng)
+        };
+        grid.push(tile);
+      }
+    }
+    self.grid = grid;
+    self.width = width;
+    self.height = height;
+  }
+
+  fn retune(&mut self, tuning: &Tuning) {
+    self.shreek_ticks = tuning.shreek_ticks;
+  }
 }
@@ -0,0 +1,149 @@
+pub const DEFAULT_MESSAGE: &str = "Happy\nBirthday\nEugenia";
+
+/// Columns kept clear on either side of the message.
+const MARGIN: usize = 4;
+
+/// A message laid out in a window.
+pub struct Greeting {
+  /// Each letter of the message, and where it goes.
+  pub letters: Vec<(char, (i32, i32))>,
+  /// The top left corner and size of the smallest rectangle holding every
+  /// letter.
+  pub bounds: ((i32, i32), (i32, i32)),
+}
+
+/// Where the letters of a greeting went when it was laid out again.
+pub struct Relayout {
+  moves: Vec<((i32, i32), (i32, i32))>,
+}
+
+impl Relayout {
+  /// Pairs up the letters of `from` and `to`, which come in the same order,
+  /// though short windows leave some of them out.
+  pub fn new(from: &Greeting, to: &Greeting) -> Self {
+    let moves = from
+      .letters
+      .iter()
+      .zip(&to.letters)
+      .map(|(&(_, from), &(_, to))| (from, to))
+      .collect();
+    Self { moves }
+  }
+
+  /// Where the letter at `pos` goes now, if there is one there.
+  pub fn moved(&self, pos: (i32, i32)) -> Option<(i32, i32)> {
+    self
+      .moves
+      .iter()
+      .find(|&&(from, _)| from == pos)
+      .map(|&(_, to)| to)
+  }
+}
+
+/// Lays out `message` for a `width` x `height` window. Lines are word-wrapped to fit the window, centered, and
+/// placed on every other row starting an eighth of the way down. If there
+/// aren't enough rows for that, they go on every row instead, and lines that
+/// still run off the bottom are left out.
+pub fn layout(message: &str, width: u32, height: u32) -> Greeting {
+  let max_len = (width as usize).saturating_sub(2 * MARGIN).max(1);
+  let lines: Vec<_> = message
+    .lines()
+    .flat_map(|line| wrap(line, max_len))
+    .collect();
+  let top = height as usize / 8;
+  let rows = height as usize - top;
+  let spacing = if 2 * lines.len() <= rows + 1 { 2 } else { 1 };
+  let letters: Vec<_> = lines
+    .into_iter()
+    .take(rows)
+    .enumerate()
+    .flat_map(|(row, line)| {
+      let x = (width as i32 - line.len() as i32) / 2;
+      let y = (top + spacing * row) as i32;
+      line
+        .into_iter()
+        .enumerate()
+        .filter(|(_, c)| !c.is_whitespace())
+        .map(move |(col, c)| (c, (x + col as i32, y)))
+    })
+    .collect();
+
+  let bounds = match letters.first() {
+    Some(&(_, first)) => {
+      let (min, max) = letters.iter().fold(
+        (first, first),
+        |((min_x, min_y), (max_x, max_y)), &(_, (x, y))| {
+          ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+        },
+      );
+      (min, (max.0 - min.0 + 1, max.1 - min.1 + 1))
+    }
+    None => ((width as i32 / 2, height as i32 / 2), (0, 0)),
+  };
+  Greeting { letters, bounds }
+}
+
+/// Splits `line` into lines of at most `max_len` characters, breaking between
+/// words where possible. Blank lines are kept, to space out paragraphs.
+fn wrap(line: &str, max_len: usize) -> Vec<Vec<char>> {
+  let mut lines = vec![Vec::new()];
+  for word in line.split_whitespace() {
+    let mut word: Vec<char> = word.chars().collect();
+    let cur = lines.last_mut().unwrap();
+    if !cur.is_empty() && cur.len() + 1 + word.len() <= max_len {
+      cur.push(' ');
+      cur.append(&mut word);
+      continue;
+    }
+    if cur.is_empty() {
+      lines.pop();
+    }
+    while word.len() > max_len {
+      let rest = word.split_off(max_len);
+      lines.push(word);
+      word = rest;
+    }
+    lines.push(word);
+  }
+  lines
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rows(letters: &[(char, (i32, i32))]) -> Vec<i32> {
+    let mut rows: Vec<_> = letters.iter().map(|&(_, (_, y))| y).collect();
+    rows.dedup();
+    rows
+  }
+
+  #[test]
+  fn lines_go_on_every_other_row() {
+    let greeting = layout(DEFAULT_MESSAGE, 120, 40);
+    assert_eq!(rows(&greeting.letters), [5, 7, 9]);
+    assert_eq!(greeting.bounds, ((56, 5), (8, 5)));
+  }
+
+  #[test]
+  fn relayouts_move_letters_in_order() {
+    let from = layout("ab cd", 20, 8);
+    let to = layout("ab cd", 10, 8);
+    assert_eq!(rows(&to.letters), [1, 3]);
+    let relayout = Relayout::new(&from, &to);
+    for (&(_, pos), &(_, moved)) in from.letters.iter().zip(&to.letters) {
+      assert_eq!(relayout.moved(pos), Some(moved));
+    }
+    assert_eq!(relayout.moved((0, 0)), None);
+  }
+
+  #[test]
+  fn lines_fit_in_short_windows() {
+    // Too many lines for every other row, but they fit on every row.
+    assert_eq!(rows(&layout("a\nb\nc\nd", 20, 6).letters), [0, 1, 2, 3]);
+    // Lines that don't fit at all are left out.
+    let letters = layout("a\nb\nc\nd\ne\nf\ng\nh", 20, 6).letters;
+    assert_eq!(rows(&letters), [0, 1, 2, 3, 4, 5]);
+    assert_eq!(letters.last().map(|&(c, _)| c), Some('f'));
+  }
+}
@@ -1,59 +1,66 @@
-use termion::color::{self, AnsiValue};
+use termion::{color, event::Key};
 
 use crate::{
-  entity::Entity,
-  util::{explosion_path, move_per_radiate, Draw, Radiate},
+  animation::{AnimationClip, Animator},
+  asset::Assets,
+  entity::{Bounds, Entity},
+  force::ForceFields,
+  greeting::Relayout,
+  particle::ParticleSystem,
+  physics::{Material, Solids},
+  sprite::Sprite,
+  tuning::Tuning,
+  util::{explosion_path, Draw},
   water::Water,
 };
 
+/// The width and height of the hole.
+pub const SIZE: (i32, i32) = (5, 4);
 const Z_IDX: i32 = 20;
 const DEBRIS_Z_IDX: i32 = 6;
 const FLYING_DEBRIS_Z_IDX: i32 = 28;
-
-#[rustfmt::skip]
-const HOLE: [&str; 4] = [
-  r#" ===L"#,
-  r#"H   H"#,
-  r#"HL _H"#,
-  r#" === "#,
-];
-
-const fn const_rgb(r: u8, g: u8, b: u8) -> color::AnsiValue {
-  AnsiValue(16 + 36 * r + 6 * g + b)
-}
-
-const KAZOO: [(char, (i32, i32), color::AnsiValue, color::AnsiValue); 11] = [
-  ('/', (0, 0), const_rgb(4, 0, 0), const_rgb(4, 0, 0)),
-  ('\\', (-1, -1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('|', (0, -1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('/', (1, -1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('-', (-1, 0), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('-', (-2, 0), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('-', (1, 0), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('-', (2, 0), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('/', (-1, 1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('|', (0, 1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-  ('\\', (1, 1), const_rgb(5, 5, 5), const_rgb(2, 2, 2)),
-];
+/// Clumps of dirt barely bounce, and don't slide far.
+const DIRT_MATERIAL: Material = Material {
+  restitution: 0.2,
+  friction: 0.6,
+};
 
 pub struct Hole {
   t: usize,
   pos: (i32, i32),
   queued_dirt: Vec<(bool, char, (i32, i32))>,
-  flung_dirt: Vec<(usize, bool, char, (i32, i32))>,
+  dirt: ParticleSystem,
   kazoo: Option<(usize, (i32, i32))>,
-  radiate: Option<Radiate>,
+  sprite: Sprite,
+  /// The kazoo in each of the colors it flashes between.
+  kazoo_sprites: [Sprite; 2],
+  /// Which of the kazoo sprites is shown.
+  kazoo_flash: Animator<usize, ()>,
 }
 
 impl Hole {
-  pub fn new(pos: (i32, i32)) -> Self {
+  pub fn new(pos: (i32, i32), assets: &Assets, forces: &ForceFields) -> Self {
+    let mut kazoo_flash = Animator::new(0);
+    kazoo_flash.play(AnimationClip::looping(vec![(0, 30), (1, 30)]), None);
     Self {
       t: 0,
       pos,
       queued_dirt: Vec::new(),
-      flung_dirt: Vec::new(),
+      dirt: ParticleSystem::new(
+        Self::center(pos),
+        color::AnsiValue::rgb(2, 1, 0),
+        DEBRIS_Z_IDX,
+        forces,
+      )
+      .with_flying_z(FLYING_DEBRIS_Z_IDX)
+      .with_physics(DIRT_MATERIAL),
       kazoo: None,
-      radiate: None,
+      sprite: assets.get("hole").sprite().with_z(Z_IDX),
+      kazoo_sprites: [
+        assets.get("kazoo").sprite().with_z(Z_IDX),
+        assets.get("kazoo").variant("dim").with_z(Z_IDX),
+      ],
+      kazoo_flash,
     }
   }
 
@@ -63,14 +70,38 @@ impl Hole {
     (0..5).contains(&dx) && (0..4).contains(&dy)
   }
 
+  /// Where dirt and the kazoo are flung out from.
+  fn center(pos: (i32, i32)) -> (i32, i32) {
+    (pos.0 + 2, pos.1 + 2)
+  }
+
+  /// The top left corner and size of the hole.
+  pub fn footprint(&self) -> ((i32, i32), (i32, i32)) {
+    (self.pos, SIZE)
+  }
+
+  /// Moves the letters of the greeting dug out of the hole, or still to be.
+  pub fn move_letters(&mut self, relayout: &Relayout) {
+    for (_, _, pos) in self
+      .queued_dirt
+      .iter_mut()
+      .filter(|(targeted, _, _)| *targeted)
+    {
+      if let Some(to) = relayout.moved(*pos) {
+        *pos = to;
+      }
+    }
+    self.dirt.move_letters(relayout);
+  }
+
   pub fn add_dirt(&mut self, dirt: Vec<(bool, char, (i32, i32))>) {
     self.queued_dirt = dirt;
   }
 
   pub fn fling(&mut self) -> bool {
     match self.queued_dirt.pop() {
-      Some(dirt) => {
-        self.flung_dirt.push((self.t, dirt.0, dirt.1, dirt.2));
+      Some((targeted, c, pos)) => {
+        self.dirt.emit(c, pos, targeted);
         true
       }
       None => {
@@ -84,96 +115,28 @@ impl Hole {
     self.kazoo = self.kazoo.map(|(t, _)| (t, pos))
   }
 
-  pub fn radiate(&mut self, pos: (i32, i32)) {
-    self.radiate = Some(Radiate { t: self.t, pos });
-  }
-
-  fn debris_pos(
-    &self,
-    (t, targeted, _, (x, y)): &(usize, bool, char, (i32, i32)),
-  ) -> ((i32, i32), bool) {
-    let mut pos = explosion_path(
-      (self.t - t) as f32,
-      (*x, *y),
-      (self.pos.0 + 2, self.pos.1 + 2),
-    );
-    let resting = pos == (*x, *y);
-    if !targeted {
-      pos = move_per_radiate(&self.radiate, self.t, pos);
-    }
-    (pos, resting)
+  pub fn simulate(&mut self, solids: &Solids) {
+    self.dirt.simulate(solids);
   }
 
   pub fn maybe_dunk(&mut self, water: &mut Water) {
-    self.flung_dirt = self
-      .flung_dirt
-      .clone()
-      .into_iter()
-      .filter(|&(t, targeted, c, pos)| {
-        let (pos, resting) = self.debris_pos(&(t, targeted, c, pos));
-        if resting && water.is_wet(pos) {
-          water.click(pos.0 as u32, pos.1 as u32);
-          false
-        } else {
-          true
-        }
-      })
-      .collect();
+    self.dirt.maybe_dunk(water);
   }
 }
 
 impl Entity for Hole {
   fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (crate::util::Draw, (i32, i32))> + '_> {
     Box::new(
-      HOLE
-        .iter()
-        .enumerate()
-        .flat_map(move |(y, row)| {
-          let y = y as i32 + self.pos.1;
-
-          row.chars().enumerate().filter_map(move |(x, c)| {
-            let x = x as i32 + self.pos.0;
-            if (x == 0 || x == 3) && (y == 0 || y == 3) && c == ' ' {
-              return None;
-            }
-
-            Some((
-              Draw::new(c)
-                .with_fg(color::AnsiValue::rgb(2, 1, 0))
-                .with_z(Z_IDX),
-              (x, y),
-            ))
-          })
-        })
-        .chain(self.flung_dirt.iter().map(|(t, targeted, c, (x, y))| {
-          let (pos, resting) = self.debris_pos(&(*t, *targeted, *c, (*x, *y)));
-          let z_idx = if resting {
-            DEBRIS_Z_IDX
-          } else {
-            FLYING_DEBRIS_Z_IDX
-          };
-          (
-            Draw::new(*c)
-              .with_fg(color::AnsiValue::rgb(2, 1, 0))
-              .with_z(z_idx + if *targeted { 1 } else { 0 }),
-            pos,
-          )
-        }))
+      self
+        .sprite
+        .draw(self.pos)
+        .chain(self.dirt.iterate_tiles())
         .chain(match self.kazoo {
           Some((kazoo_t, kazoo_pos)) => {
-            Box::new(KAZOO.iter().map(move |(c, (dx, dy), color1, color2)| {
-              let (x, y) = explosion_path(
-                (self.t - kazoo_t) as f32,
-                kazoo_pos,
-                (self.pos.0 + 2, self.pos.1 + 2),
-              );
-              let color = if (self.t / 30) % 2 == 0 {
-                *color1
-              } else {
-                *color2
-              };
-              (Draw::new(*c).with_fg(color).with_z(Z_IDX), (x + dx, y + dy))
-            })) as Box<dyn Iterator<Item = (Draw, (i32, i32))>>
+            let (x, y) =
+              explosion_path((self.t - kazoo_t) as f32, kazoo_pos, Self::center(self.pos));
+            let sprite = &self.kazoo_sprites[*self.kazoo_flash.frame()];
+            Box::new(sprite.draw((x - 2, y - 1))) as Box<dyn Iterator<Item = (Draw, (i32, i32))>>
           }
           None => Box::new([].into_iter()),
         }),
@@ -182,9 +145,31 @@ impl Entity for Hole {
 
   fn tick(&mut self, t: usize) {
     self.t = t;
+    self.dirt.tick(t);
+    self.kazoo_flash.tick(t);
+  }
+
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
   }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn retune(&mut self, tuning: &Tuning) {
+    self.pos = tuning.hole;
+    self.dirt.set_origin(Self::center(self.pos));
+  }
 }
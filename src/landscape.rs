@@ -1,22 +1,43 @@
 use rand::Rng;
+use termion::event::Key;
 
-use crate::{entity::Entity, grass::Grass, water::Water};
+use crate::{
+  entity::{dispatch, Bounds, Entity},
+  grass::Grass,
+  nav::NavGrid,
+  physics::Solids,
+  tuning::Tuning,
+  water::Water,
+};
 
 pub struct Landscape {
   grass: Grass,
   water: Water,
+  /// The grass along the water, which is a bank that things can only get
+  /// over by flying.
+  solids: Solids,
 }
 
 impl Landscape {
-  pub fn new<R: Rng>(width: u32, height: u32, rand: &mut R) -> Self {
-    let mut grass = Grass::new(width, height, rand);
-    let mut water = Water::new(width, height);
+  pub fn new<R: Rng>(width: u32, height: u32, tuning: &Tuning, rand: &mut R) -> Self {
+    let mut landscape = Self {
+      grass: Grass::new(width, height, tuning, rand),
+      water: Water::new(width, height, tuning),
+      solids: Solids::new(width, height),
+    };
+    landscape.carve_shoreline(width, height);
+    landscape
+  }
+
+  fn carve_shoreline(&mut self, width: u32, height: u32) {
+    let grass = &mut self.grass;
+    let water = &mut self.water;
 
     let shoreline = height * 5 / 6;
     (shoreline..height).for_each(|y| (0..width).for_each(|x| grass.delete_tile(x, y)));
-    (width / 3..width).for_each(|x| grass.delete_tile(x, shoreline - 1));
-    (width / 2..width).for_each(|x| grass.delete_tile(x, shoreline - 2));
-    (width * 4 / 5..width).for_each(|x| grass.delete_tile(x, shoreline - 3));
+    (width / 3..width).for_each(|x| grass.delete_tile(x, shoreline.saturating_sub(1)));
+    (width / 2..width).for_each(|x| grass.delete_tile(x, shoreline.saturating_sub(2)));
+    (width * 4 / 5..width).for_each(|x| grass.delete_tile(x, shoreline.saturating_sub(3)));
 
     (0..height).for_each(|y| {
       (0..width).for_each(|x| {
@@ -26,7 +47,37 @@ impl Landscape {
       })
     });
 
-    Self { grass, water }
+    self.solids = Solids::new(width, height);
+    for y in 0..height as i32 {
+      for x in 0..width as i32 {
+        let bank = !self.water.is_wet((x, y))
+          && [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .any(|(dx, dy)| self.water.is_wet((x + dx, y + dy)));
+        if bank {
+          self.solids.fill((x, y));
+        }
+      }
+    }
+  }
+
+  /// A grid of the window where the water can't be walked on.
+  pub fn nav_grid(&self) -> NavGrid {
+    let (width, height) = self.water.size();
+    let mut grid = NavGrid::new(width, height);
+    for y in 0..height as i32 {
+      for x in 0..width as i32 {
+        if self.water.is_wet((x, y)) {
+          grid.block((x, y));
+        }
+      }
+    }
+    grid
+  }
+
+  /// The field the size of the window, walled in by its edges.
+  pub fn solids(&self) -> Solids {
+    self.solids.clone()
   }
 
   pub fn water_mut(&mut self) -> &mut Water {
@@ -36,6 +87,10 @@ impl Landscape {
   pub fn shreek(&mut self, pos: (i32, i32)) {
     self.grass.shreek(pos);
   }
+
+  fn children_mut(&mut self) -> [&mut dyn Entity; 2] {
+    [&mut self.grass, &mut self.water]
+  }
 }
 
 impl Entity for Landscape {
@@ -48,18 +103,37 @@ impl Entity for Landscape {
     self.water.tick(t);
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    self.grass.click(x, y);
-    self.water.click(x, y);
+  fn bounds(&self) -> Option<Bounds> {
+    Bounds::enclosing([self.grass.bounds(), self.water.bounds()])
+  }
+
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.click(x, y)).is_some()
+  }
+
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.drag(x, y)).is_some()
+  }
+
+  fn release(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.release(x, y)).is_some()
+  }
+
+  fn key(&mut self, key: Key) -> bool {
+    self
+      .children_mut()
+      .into_iter()
+      .any(|entity| entity.key(key))
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
-    self.grass.drag(x, y);
-    self.water.drag(x, y);
+  fn resize(&mut self, width: u32, height: u32) {
+    self.grass.resize(width, height);
+    self.water.resize(width, height);
+    self.carve_shoreline(width, height);
   }
 
-  fn release(&mut self, x: u32, y: u32) {
-    self.grass.release(x, y);
-    self.water.release(x, y);
+  fn retune(&mut self, tuning: &Tuning) {
+    self.grass.retune(tuning);
+    self.water.retune(tuning);
   }
 }
@@ -1,93 +1,436 @@
+mod animation;
+mod args;
+mod asset;
 mod basket;
 mod bunny;
 mod carrot;
+mod cast;
+mod color;
+mod cue;
 mod dialog;
 mod entity;
+mod force;
+#[cfg(test)]
+mod golden;
 mod grass;
+mod greeting;
 mod hole;
 mod landscape;
+mod nav;
+mod overlay;
+mod particle;
 mod peach;
+mod physics;
+mod replay;
+mod restore;
 mod scene;
+mod sprite;
+mod story;
 mod track;
 mod train;
 mod train_scene;
+mod tuning;
+mod tween;
 mod util;
 mod water;
 mod window;
 mod zoom;
 
-use std::sync::Mutex;
-use std::time::SystemTime;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
+use args::{Args, USAGE};
+use asset::Assets;
 use bunny::Bunny;
+use cast::Asciicast;
+use color::ColorMode;
 use entity::Entity;
+use greeting::DEFAULT_MESSAGE;
+use overlay::ErrorOverlay;
 use rand::{rngs, SeedableRng};
+use replay::{key_name, Input, InputRecorder, Replay};
 use scene::Scene;
+use signal_hook::consts::{SIGINT, SIGTERM, SIGWINCH};
+use story::{Story, DEFAULT_STORY};
 use termion::async_stdin;
 use termion::cursor::HideCursor;
 use termion::event::{Event, Key, MouseEvent};
-use termion::input::{MouseTerminal, TermRead};
-use termion::raw::IntoRawMode;
+use termion::input::{Events, MouseTerminal, TermRead};
+use termion::screen::IntoAlternateScreen;
+use termion::AsyncReader;
+use tuning::TuningFile;
+use window::Window;
 
-fn main() {
-  let guard = pprof::ProfilerGuardBuilder::default()
-    .frequency(1000)
-    .blocklist(&["libc", "libgcc", "pthread", "vdso"])
-    .build()
-    .unwrap();
-
-  let stdout = HideCursor::from(MouseTerminal::from(
-    std::io::stdout().lock().into_raw_mode().unwrap(),
-  ));
-  let mut window = window::Window::new(stdout, 120, 40);
-  let mut stdin = async_stdin().events();
-  let done = Mutex::new(false);
+const DEFAULT_FPS: u32 = 50;
+/// The seed from the name "Eugenia".
+const DEFAULT_SEED: u64 = 27418995609531717;
+const DEFAULT_SIZE: (u32, u32) = (120, 40);
+/// Where panics are reported, besides stderr.
+const CRASH_FILE: &str = "term-anim-crash.log";
+
+/// Input from the terminal the animation is running in.
+struct Terminal {
+  stdin: Events<AsyncReader>,
+  resized: Arc<AtomicBool>,
+  /// Set on SIGINT or SIGTERM, to quit as if 'q' were pressed.
+  interrupted: Arc<AtomicBool>,
+}
+
+/// Forwards a mouse event to the scene, converting from the terminal's 1-based
+/// coordinates.
+fn handle_mouse(scene: &mut Scene, me: MouseEvent) {
+  match me {
+    MouseEvent::Press(_, x, y) => {
+      scene.click(x as u32 - 1, y as u32 - 1);
+    }
+    MouseEvent::Hold(x, y) => {
+      scene.drag(x as u32 - 1, y as u32 - 1);
+    }
+    MouseEvent::Release(x, y) => {
+      scene.release(x as u32 - 1, y as u32 - 1);
+    }
+  }
+}
+
+/// What was drawn over a run.
+struct RunSummary {
+  /// The text of the last frame.
+  final_frame: String,
+  frames: usize,
+  /// The bytes sent to the terminal over every frame.
+  bytes: usize,
+  /// Whether the story reached its end, rather than being quit.
+  finished: bool,
+}
 
-  let mut r = rngs::StdRng::seed_from_u64(27418995609531717u64);
+/// Runs the animation until it finishes, the user quits, or the frame limit is
+/// hit, and returns what was drawn. Without a terminal, frames are
+/// rendered back to back instead of in real time.
+///
+/// Inputs come from the terminal, or from `replay` if one is given, in which
+/// case the terminal is only watched for 'q'. `on_frame` is called with each
+/// frame once it has been rendered.
+fn run<W: Write>(
+  mut window: Window<W>,
+  mut terminal: Option<Terminal>,
+  mut replay: Option<Replay>,
+  story: Story,
+  assets: &Assets,
+  args: &Args,
+  mut on_frame: impl FnMut(usize, &Window<W>),
+) -> RunSummary {
+  let seed = match &replay {
+    Some(replay) => replay.seed(),
+    None => args.seed.unwrap_or(DEFAULT_SEED),
+  };
+  let frame_period = Duration::from_secs(1) / args.fps.unwrap_or(DEFAULT_FPS);
+  let message = args.message.as_deref().unwrap_or(DEFAULT_MESSAGE);
+  let done = Mutex::new(false);
+  let mut r = rngs::StdRng::seed_from_u64(seed);
 
-  let bunny = Bunny::new(window.width(), window.height(), &mut r, &done);
+  let bunny = Bunny::new(
+    window.width(),
+    window.height(),
+    message,
+    story,
+    assets,
+    &mut r,
+    &done,
+  );
 
   let mut scene = Scene::new();
   scene.add_entity(Box::new(bunny));
+  let mut tuning_file = args.tuning.as_deref().map(TuningFile::new);
+  let mut overlay = ErrorOverlay::new();
+
+  let mut cast = args.record.as_ref().map(|path| {
+    Asciicast::new(create_file(path), window.width(), window.height())
+      .expect("Failed to write recording")
+  });
+  let mut recorder = args.record_input.as_ref().map(|path| {
+    let size = (window.width(), window.height());
+    InputRecorder::new(create_file(path), seed, size, &args.content_options())
+      .expect("Failed to write input recording")
+  });
+  let run_start = Instant::now();
+  let mut frames = 0;
+  let mut bytes = 0;
 
   'outer: for t in 0usize.. {
     let start = SystemTime::now();
-    if *done.lock().unwrap() {
-      break;
-    }
-    for evt in stdin.by_ref() {
-      match evt {
-        Ok(Event::Key(Key::Char('q'))) => break 'outer,
-        Ok(Event::Mouse(me)) => match me {
-          MouseEvent::Press(_, x, y) => {
-            scene.click(x as u32 - 1, y as u32 - 1);
-          }
-          MouseEvent::Hold(x, y) => {
-            scene.drag(x as u32 - 1, y as u32 - 1);
+    let time = match terminal {
+      Some(_) => run_start.elapsed(),
+      None => frame_period * t as u32,
+    };
+
+    let mut inputs = Vec::new();
+    if *done.lock().unwrap() || args.frames.is_some_and(|frames| t >= frames) {
+      inputs.push(Input::Quit);
+    }
+    if let Some(terminal) = &mut terminal {
+      for evt in terminal.stdin.by_ref() {
+        match evt {
+          Ok(Event::Key(Key::Char('q') | Key::Ctrl('c'))) | Err(_) => inputs.push(Input::Quit),
+          Ok(Event::Mouse(me)) if replay.is_none() => inputs.push(Input::Mouse(me)),
+          Ok(Event::Key(key)) if replay.is_none() && key_name(key).is_some() => {
+            inputs.push(Input::Key(key))
           }
-          MouseEvent::Release(x, y) => {
-            scene.release(x as u32 - 1, y as u32 - 1);
+          _ => {}
+        }
+      }
+      if terminal.interrupted.load(Ordering::Relaxed) {
+        inputs.push(Input::Quit);
+      }
+      if terminal.resized.swap(false, Ordering::Relaxed) && replay.is_none() {
+        if let Ok((width, height)) = termion::terminal_size() {
+          inputs.push(Input::Resize(width as u32, height as u32));
+        }
+      }
+    }
+    if let Some(replay) = &mut replay {
+      if terminal.is_none() && replay.is_finished() {
+        inputs.push(Input::Quit);
+      }
+      inputs.extend(replay.inputs_until(t));
+    }
+    // Reloads are inputs too, so a recorded run replays with the same tuning.
+    match tuning_file.as_mut().and_then(TuningFile::poll) {
+      Some(Ok(tuning)) => inputs.push(Input::Retune(tuning)),
+      Some(Err(err)) => overlay.show(format!("error: {err}")),
+      None => {}
+    }
+
+    for input in inputs {
+      if let Some(recorder) = &mut recorder {
+        recorder
+          .record(t, &input)
+          .expect("Failed to write input recording");
+      }
+      match input {
+        Input::Mouse(me) => handle_mouse(&mut scene, me),
+        Input::Key(key) => {
+          scene.key(key);
+        }
+        Input::Resize(width, height) => {
+          window.resize(width, height).expect("Failed to resize");
+          scene.resize(width, height);
+          if let Some(cast) = &mut cast {
+            cast
+              .resize(time, width, height)
+              .expect("Failed to write recording");
           }
-        },
-        Err(_) => break 'outer,
-        _ => {}
+        }
+        Input::Retune(tuning) => {
+          scene.retune(&tuning);
+          overlay.clear();
+        }
+        Input::Quit => break 'outer,
       }
     }
+
     window.reset();
     scene.tick(t);
     scene.render(&mut window);
-    window.render().expect("Failed 2 render");
-    let end = SystemTime::now();
+    overlay.render(&mut window);
+    bytes += window.render().expect("Failed 2 render");
+    frames += 1;
+    on_frame(t, &window);
+    if let Some(cast) = &mut cast {
+      cast
+        .output(time, window.last_frame())
+        .expect("Failed to write recording");
+    }
 
-    let sleep_duration =
-      std::time::Duration::from_millis(20).saturating_sub(end.duration_since(start).unwrap());
-    std::thread::sleep(sleep_duration);
+    if terminal.is_some() {
+      let end = SystemTime::now();
+      let sleep_duration = frame_period.saturating_sub(end.duration_since(start).unwrap());
+      std::thread::sleep(sleep_duration);
+    }
   }
 
+  if let Some(cast) = &mut cast {
+    cast.finish().expect("Failed to write recording");
+  }
+  if let Some(recorder) = &mut recorder {
+    recorder.finish().expect("Failed to write input recording");
+  }
   window.cleanup().expect("Failed to cleanup");
+  let finished = *done.lock().unwrap();
+  RunSummary {
+    final_frame: window.to_text(),
+    frames,
+    bytes,
+    finished,
+  }
+}
+
+fn create_file(path: &Path) -> BufWriter<File> {
+  let file =
+    File::create(path).unwrap_or_else(|err| panic!("Failed to create {}: {err}", path.display()));
+  BufWriter::new(file)
+}
+
+fn main() {
+  let mut args = match Args::parse(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(err) => {
+      eprintln!("error: {err}\n\n{USAGE}");
+      std::process::exit(2);
+    }
+  };
+  if args.help {
+    print!("{USAGE}");
+    return;
+  }
+  if let Some(path) = &args.message_file {
+    match std::fs::read_to_string(path) {
+      Ok(message) => args.message = Some(message),
+      Err(err) => {
+        eprintln!("error: Failed to read {}: {err}", path.display());
+        std::process::exit(1);
+      }
+    }
+  }
+  let replay = args
+    .replay
+    .as_deref()
+    .map(|path| -> Result<_, String> {
+      let replay = Replay::load(path)?;
+      replay
+        .check_options(&args.content_options())
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+      Ok(replay)
+    })
+    .transpose();
+  let replay = match replay {
+    Ok(replay) => replay,
+    Err(err) => {
+      eprintln!("error: {err}");
+      std::process::exit(1);
+    }
+  };
+
+  let story = match &args.story {
+    Some(path) => Story::load(path),
+    None => Story::parse(DEFAULT_STORY),
+  };
+  let story = match story {
+    Ok(story) => story,
+    Err(err) => {
+      eprintln!("error: {err}");
+      std::process::exit(1);
+    }
+  };
+
+  let assets = match &args.assets {
+    Some(dir) => Assets::load(dir),
+    None => Ok(Assets::builtin()),
+  };
+  let assets = match assets {
+    Ok(assets) => assets,
+    Err(err) => {
+      eprintln!("error: {err}");
+      std::process::exit(1);
+    }
+  };
+
+  let guard = args.profile.as_ref().map(|_| {
+    pprof::ProfilerGuardBuilder::default()
+      .frequency(1000)
+      .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+      .build()
+      .expect("Failed to start the profiler")
+  });
+
+  let summary = if args.headless {
+    let (width, height) = match &replay {
+      Some(replay) => replay.size(),
+      None => (
+        args.width.unwrap_or(DEFAULT_SIZE.0),
+        args.height.unwrap_or(DEFAULT_SIZE.1),
+      ),
+    };
+    run(
+      Window::headless(width, height),
+      None,
+      replay,
+      story,
+      &assets,
+      &args,
+      |_, _| {},
+    )
+  } else {
+    restore::install(PathBuf::from(CRASH_FILE)).expect("Failed to enter raw mode");
+    let stdout = HideCursor::from(MouseTerminal::from(
+      std::io::stdout()
+        .lock()
+        .into_alternate_screen()
+        .expect("Failed to switch to the alternate screen"),
+    ));
+    let (width, height) = match &replay {
+      Some(replay) => replay.size(),
+      None => {
+        let (term_width, term_height) = termion::terminal_size()
+          .map_or(DEFAULT_SIZE, |(width, height)| {
+            (width as u32, height as u32)
+          });
+        (
+          args.width.unwrap_or(term_width),
+          args.height.unwrap_or(term_height),
+        )
+      }
+    };
+    let window = Window::new(stdout, width, height).with_color_mode(ColorMode::detect());
+
+    let resized = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(SIGWINCH, Arc::clone(&resized))
+      .expect("Failed to register SIGWINCH handler");
+    let interrupted = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+      signal_hook::flag::register(signal, Arc::clone(&interrupted))
+        .expect("Failed to register signal handler");
+    }
 
-  if let Ok(report) = guard.report().build() {
-    let file = std::fs::File::create("prof.svg").unwrap();
-    report.flamegraph(file).unwrap();
+    let terminal = Terminal {
+      stdin: async_stdin().events(),
+      resized,
+      interrupted,
+    };
+    let summary = run(
+      window,
+      Some(terminal),
+      replay,
+      story,
+      &assets,
+      &args,
+      |_, _| {},
+    );
+    restore::restore();
+    summary
   };
+  if args.summary {
+    print!("{}", summary.final_frame);
+  }
+  if args.summary || args.profile.is_some() {
+    eprintln!(
+      "Rendered {} frames in {} bytes, {} per frame",
+      summary.frames,
+      summary.bytes,
+      summary.bytes / summary.frames.max(1)
+    );
+  }
+  if args.summary && !summary.finished {
+    eprintln!("Quit before the story finished");
+  }
+
+  if let (Some(guard), Some(path)) = (guard, &args.profile) {
+    let report = guard.report().build().expect("Failed to build profile");
+    report
+      .flamegraph(create_file(path))
+      .expect("Failed to write profile");
+  }
 }
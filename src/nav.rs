@@ -0,0 +1,164 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// The cost of a step across a column, down a row, and diagonally. Rows are
+/// about twice as tall as columns are wide.
+const ACROSS_COST: u32 = 10;
+const DOWN_COST: u32 = 20;
+const DIAGONAL_COST: u32 = 22;
+
+/// Which tiles of the window can be walked on.
+pub struct NavGrid {
+  width: u32,
+  height: u32,
+  blocked: Vec<bool>,
+}
+
+impl NavGrid {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self {
+      width,
+      height,
+      blocked: vec![false; (width * height) as usize],
+    }
+  }
+
+  fn idx(&self, (x, y): (i32, i32)) -> Option<usize> {
+    if (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+      Some((x as u32 + y as u32 * self.width) as usize)
+    } else {
+      None
+    }
+  }
+
+  fn pos(&self, idx: usize) -> (i32, i32) {
+    (
+      (idx as u32 % self.width) as i32,
+      (idx as u32 / self.width) as i32,
+    )
+  }
+
+  pub fn block(&mut self, pos: (i32, i32)) {
+    if let Some(idx) = self.idx(pos) {
+      self.blocked[idx] = true;
+    }
+  }
+
+  /// Blocks the `size` tiles with their top left corner at `pos`.
+  pub fn block_rect(&mut self, pos: (i32, i32), size: (i32, i32)) {
+    for y in pos.1..pos.1 + size.1 {
+      for x in pos.0..pos.0 + size.0 {
+        self.block((x, y));
+      }
+    }
+  }
+
+  /// Whether something of `size` fits with its top left corner at `pos`.
+  fn fits(&self, pos: (i32, i32), size: (i32, i32)) -> bool {
+    (pos.1..pos.1 + size.1).all(|y| {
+      (pos.0..pos.0 + size.0).all(|x| self.idx((x, y)).is_some_and(|idx| !self.blocked[idx]))
+    })
+  }
+
+  /// Whether something of `size` can move in a straight line from `from` to
+  /// `to`.
+  fn clear_line(&self, from: (i32, i32), to: (i32, i32), size: (i32, i32)) -> bool {
+    let dx = (to.0 - from.0) as f32;
+    let dy = (to.1 - from.1) as f32;
+    // Sample finely enough to hit every tile the line passes through.
+    let samples = 2 * (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+    (0..=samples).all(|sample| {
+      let s = sample as f32 / samples as f32;
+      let pos = (
+        (from.0 as f32 + dx * s).round() as i32,
+        (from.1 as f32 + dy * s).round() as i32,
+      );
+      self.fits(pos, size)
+    })
+  }
+
+  /// The cheapest path for something of `size` from `from` to `to`, as the
+  /// corners to walk between in straight lines, starting with `from`. It may
+  /// start out somewhere it doesn't fit, so it can walk out of it. Returns
+  /// `None` if `to` can't be reached.
+  pub fn find_path(
+    &self,
+    from: (i32, i32),
+    to: (i32, i32),
+    size: (i32, i32),
+  ) -> Option<Vec<(i32, i32)>> {
+    let start = self.idx(from)?;
+    let goal = self.idx(to)?;
+    if !self.fits(to, size) {
+      return None;
+    }
+
+    let heuristic = |(x, y): (i32, i32)| {
+      let across = (x - to.0).unsigned_abs();
+      let down = (y - to.1).unsigned_abs();
+      let diagonal = across.min(down);
+      DIAGONAL_COST * diagonal + ACROSS_COST * (across - diagonal) + DOWN_COST * (down - diagonal)
+    };
+
+    let mut cost = vec![u32::MAX; self.blocked.len()];
+    let mut came_from = vec![usize::MAX; self.blocked.len()];
+    let mut open = BinaryHeap::new();
+    cost[start] = 0;
+    open.push(Reverse((heuristic(from), start)));
+
+    while let Some(Reverse((_, idx))) = open.pop() {
+      if idx == goal {
+        break;
+      }
+      let pos = self.pos(idx);
+      for (dx, dy) in [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+      ] {
+        let next = (pos.0 + dx, pos.1 + dy);
+        let Some(next_idx) = self.idx(next).filter(|_| self.fits(next, size)) else {
+          continue;
+        };
+        let step = match (dx, dy) {
+          (_, 0) => ACROSS_COST,
+          (0, _) => DOWN_COST,
+          _ => DIAGONAL_COST,
+        };
+        let next_cost = cost[idx] + step;
+        if next_cost < cost[next_idx] {
+          cost[next_idx] = next_cost;
+          came_from[next_idx] = idx;
+          open.push(Reverse((next_cost + heuristic(next), next_idx)));
+        }
+      }
+    }
+    if cost[goal] == u32::MAX {
+      return None;
+    }
+
+    let mut tiles = vec![to];
+    let mut idx = goal;
+    while idx != start {
+      idx = came_from[idx];
+      tiles.push(self.pos(idx));
+    }
+    tiles.reverse();
+
+    // Cut corners wherever there is a straight line to a later tile.
+    let mut corners = vec![from];
+    let mut corner_idx = 0;
+    while corner_idx + 1 < tiles.len() {
+      corner_idx = (corner_idx + 1..tiles.len())
+        .rev()
+        .find(|&idx| self.clear_line(tiles[corner_idx], tiles[idx], size))
+        .unwrap_or(corner_idx + 1);
+      corners.push(tiles[corner_idx]);
+    }
+    Some(corners)
+  }
+}
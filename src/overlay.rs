@@ -0,0 +1,75 @@
+use termion::{color, event::Key};
+
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  util::Draw,
+};
+
+/// Drawn over everything else, even the zoom.
+const Z_IDX: i32 = 2000;
+
+/// A message shown across the top of the window, for problems found while the
+/// animation is running.
+pub struct ErrorOverlay {
+  message: Option<String>,
+}
+
+impl ErrorOverlay {
+  pub fn new() -> Self {
+    Self { message: None }
+  }
+
+  pub fn show(&mut self, message: String) {
+    self.message = Some(message);
+  }
+
+  pub fn clear(&mut self) {
+    self.message = None;
+  }
+}
+
+impl Entity for ErrorOverlay {
+  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
+    Box::new(
+      self
+        .message
+        .iter()
+        .flat_map(|message| message.lines().enumerate())
+        .flat_map(|(y, line)| {
+          line.chars().enumerate().map(move |(x, c)| {
+            (
+              Draw::new(c)
+                .with_fg(color::AnsiValue::rgb(5, 5, 5))
+                .with_bg(color::AnsiValue::rgb(3, 0, 0))
+                .with_z(Z_IDX),
+              (x as i32, y as i32),
+            )
+          })
+        }),
+    )
+  }
+
+  fn tick(&mut self, _t: usize) {}
+
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, _tuning: &Tuning) {}
+}
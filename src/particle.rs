@@ -0,0 +1,275 @@
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+use termion::event::Key;
+
+use crate::{
+  color::Rgb,
+  entity::{Bounds, Entity},
+  force::ForceFields,
+  greeting::Relayout,
+  physics::{Body, Material, Solids},
+  tuning::Tuning,
+  util::{explosion_path, explosion_target_dt, Draw},
+  water::Water,
+};
+
+/// How a burst of particles is spread out: `count` random glyphs, landing
+/// within `radius` of where they are flung from.
+pub struct Emitter {
+  pub count: usize,
+  pub radius: i32,
+  pub glyphs: RangeInclusive<char>,
+}
+
+impl Emitter {
+  /// Picks where each particle of a burst from `origin` lands, never left of
+  /// or above the window.
+  fn targets<R: Rng>(&self, origin: (i32, i32), rng: &mut R) -> Vec<(char, (i32, i32))> {
+    let radius = self.radius;
+    (0..self.count)
+      .map(|_| {
+        let mut dx = rng.gen_range(-radius..=radius);
+        let mut dy = rng.gen_range(-radius..=radius);
+        while dx * dx + dy * dy > (radius * radius) || origin.0 + dx < 0 || origin.1 + dy < 0 {
+          dx = rng.gen_range(-radius..=radius);
+          dy = rng.gen_range(-radius..=radius);
+        }
+
+        let glyph = rng.gen_range(self.glyphs.clone());
+        (glyph, (origin.0 + dx, origin.1 + dy))
+      })
+      .collect()
+  }
+}
+
+struct Particle {
+  /// When the particle was flung.
+  t: usize,
+  glyph: char,
+  target: (i32, i32),
+  /// Targeted particles are letters of the greeting, which stay where they
+  /// land. The rest are moved around by the scene's force fields, and can be
+  /// dunked or disappear.
+  targeted: bool,
+  /// How the particle is flying, if it is moved by physics instead of flying
+  /// straight to its target.
+  body: Option<Body>,
+}
+
+/// Glyphs flung out from an origin, each flying over to where it lands.
+pub struct ParticleSystem {
+  t: usize,
+  origin: (i32, i32),
+  color: Rgb,
+  z_idx: i32,
+  /// The z-index of particles still in flight, if different.
+  flying_z_idx: Option<i32>,
+  /// How many times as long as they took to land untargeted particles last,
+  /// if they don't last forever.
+  lifetime: Option<usize>,
+  /// What untargeted particles are made of, if they bounce and slide around
+  /// once they land instead of stopping dead on their targets.
+  material: Option<Material>,
+  forces: ForceFields,
+  particles: Vec<Particle>,
+}
+
+impl ParticleSystem {
+  /// Targeted particles are drawn one z-index above `z_idx`.
+  pub fn new<C: Into<Rgb>>(origin: (i32, i32), color: C, z_idx: i32, forces: &ForceFields) -> Self {
+    Self {
+      t: 0,
+      origin,
+      color: color.into(),
+      z_idx,
+      flying_z_idx: None,
+      lifetime: None,
+      material: None,
+      forces: forces.clone(),
+      particles: Vec::new(),
+    }
+  }
+
+  pub fn with_flying_z(self, z_idx: i32) -> Self {
+    Self {
+      flying_z_idx: Some(z_idx),
+      ..self
+    }
+  }
+
+  pub fn with_lifetime(self, flights: usize) -> Self {
+    Self {
+      lifetime: Some(flights),
+      ..self
+    }
+  }
+
+  pub fn with_physics(self, material: Material) -> Self {
+    Self {
+      material: Some(material),
+      ..self
+    }
+  }
+
+  /// Moves where particles fly from, including those flying straight to their
+  /// targets.
+  pub fn set_origin(&mut self, origin: (i32, i32)) {
+    self.origin = origin;
+  }
+
+  /// Moves letters of the greeting, to where they go in the new layout.
+  pub fn move_letters(&mut self, relayout: &Relayout) {
+    for particle in self
+      .particles
+      .iter_mut()
+      .filter(|particle| particle.targeted)
+    {
+      if let Some(to) = relayout.moved(particle.target) {
+        particle.target = to;
+      }
+    }
+  }
+
+  pub fn emit(&mut self, glyph: char, target: (i32, i32), targeted: bool) {
+    self.particles.push(Particle {
+      t: self.t,
+      glyph,
+      target,
+      targeted,
+      body: (self.material.is_some() && !targeted).then(|| Body::launch(self.origin, target)),
+    });
+  }
+
+  /// Flings letters of the greeting to where they go in the message.
+  pub fn emit_targeted(&mut self, letters: Vec<(char, (i32, i32))>) {
+    for (glyph, target) in letters {
+      self.emit(glyph, target, true);
+    }
+  }
+
+  pub fn burst<R: Rng>(&mut self, emitter: &Emitter, rng: &mut R) {
+    for (glyph, target) in emitter.targets(self.origin, rng) {
+      self.emit(glyph, target, false);
+    }
+  }
+
+  /// Where `particle` is, and whether it has landed.
+  fn particle_pos(&self, particle: &Particle) -> ((i32, i32), bool) {
+    let (mut pos, resting) = match &particle.body {
+      Some(body) => (body.pos(), body.grounded()),
+      None => {
+        let pos = explosion_path((self.t - particle.t) as f32, particle.target, self.origin);
+        (pos, pos == particle.target)
+      }
+    };
+    // Bodies are pushed around by the force fields as they are simulated.
+    if !particle.targeted && particle.body.is_none() {
+      pos = self.forces.displace(particle.t, self.t, pos);
+    }
+    (pos, resting)
+  }
+
+  /// Moves particles with bodies on by a tick, after the force fields have
+  /// pushed them around. Particles that come to rest pile up against `solids`
+  /// and each other.
+  pub fn simulate(&mut self, solids: &Solids) {
+    let Some(material) = self.material else {
+      return;
+    };
+    for particle in self.particles.iter_mut() {
+      if let Some(body) = &mut particle.body {
+        body.push(self.forces.nudge(particle.t, self.t, body.pos()));
+      }
+    }
+    let mut solids = solids.clone();
+    for body in self
+      .particles
+      .iter()
+      .filter_map(|particle| particle.body.as_ref())
+    {
+      if body.resting() {
+        solids.fill(body.pos());
+      }
+    }
+    for body in self
+      .particles
+      .iter_mut()
+      .filter_map(|particle| particle.body.as_mut())
+    {
+      if !body.resting() {
+        body.step(&material, &solids);
+        if body.resting() {
+          solids.fill(body.pos());
+        }
+      }
+    }
+  }
+
+  /// Sinks untargeted particles that have landed in the water, making a
+  /// splash where each one went in.
+  pub fn maybe_dunk(&mut self, water: &mut Water) {
+    let particles = std::mem::take(&mut self.particles);
+    self.particles = particles
+      .into_iter()
+      .filter(|particle| {
+        let (pos, resting) = self.particle_pos(particle);
+        if !particle.targeted && resting && water.is_wet(pos) {
+          water.click(pos.0 as u32, pos.1 as u32);
+          false
+        } else {
+          true
+        }
+      })
+      .collect();
+  }
+}
+
+impl Entity for ParticleSystem {
+  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
+    Box::new(self.particles.iter().map(|particle| {
+      let (pos, resting) = self.particle_pos(particle);
+      let z_idx = match self.flying_z_idx {
+        Some(flying_z_idx) if !resting => flying_z_idx,
+        _ => self.z_idx,
+      };
+      (
+        Draw::new(particle.glyph)
+          .with_fg(self.color)
+          .with_z(z_idx + if particle.targeted { 1 } else { 0 }),
+        pos,
+      )
+    }))
+  }
+
+  fn tick(&mut self, t: usize) {
+    self.t = t;
+    if let Some(flights) = self.lifetime {
+      let origin = self.origin;
+      self.particles.retain(|particle| {
+        particle.targeted || flights * explosion_target_dt(particle.target, origin) > t - particle.t
+      });
+    }
+  }
+
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, _tuning: &Tuning) {}
+}
@@ -1,219 +1,284 @@
-use std::iter;
-
 use rand::Rng;
-use termion::color;
+use termion::{color, event::Key};
 
 use crate::{
-  entity::Entity,
-  util::{explosion_path, move_per_radiate, Draw, Radiate},
+  entity::{Bounds, Entity},
+  force::ForceFields,
+  greeting::Relayout,
+  particle::{Emitter, ParticleSystem},
+  physics::{Body, Material, Solids},
+  tuning::Tuning,
+  util::Draw,
   water::Water,
 };
 
 const Z_IDX: i32 = 30;
 const DEBRIS_Z_IDX: i32 = 6;
+/// Bits of peach are soft and sticky.
+const DEBRIS_MATERIAL: Material = Material {
+  restitution: 0.3,
+  friction: 0.4,
+};
+/// Whole peaches roll a little.
+const MATERIAL: Material = Material {
+  restitution: 0.4,
+  friction: 0.2,
+};
+/// How many ticks back drags count towards how fast a peach is thrown, and
+/// how fast it can be thrown, in columns per tick.
+const THROW_TICKS: usize = 5;
+const MAX_THROW_SPEED: f32 = 1.5;
+/// How much higher a peach is tossed for each column per tick it is thrown.
+const THROW_LIFT: f32 = 0.3;
 
-#[derive(Clone, PartialEq, Eq)]
 enum PeachState {
   Idle,
-  /// (dx, dy) is the distance to the peach from where the mouse is held.
+  /// (dx, dy) is the distance to the peach from where the mouse is held, and
+  /// `drags` are when and where the peach was dragged to, oldest first.
   Held {
     dx: i32,
     dy: i32,
+    drags: Vec<(usize, (i32, i32))>,
   },
-  Explode {
-    t: usize,
-    target_letters: Vec<(char, (i32, i32))>,
-    rand_letters: Vec<(char, (i32, i32))>,
-  },
+  /// The body is where the peach's hitbox is.
+  Thrown(Body),
+  Explode,
 }
 
 pub struct Peach {
+  t: usize,
+  /// Where the peach was put down. While sitting still it is moved from here
+  /// by the force fields that have started since `since`.
   x: i32,
   y: i32,
-  t: usize,
+  since: usize,
+  /// Where the peach sits in the basket, which it goes back to if it is
+  /// thrown into the water.
+  home: (i32, i32),
   color: color::AnsiValue,
   state: PeachState,
-  radiate: Option<Radiate>,
+  forces: ForceFields,
+  debris: ParticleSystem,
 }
 
 impl Peach {
-  pub fn new(x: i32, y: i32, color: color::AnsiValue) -> Self {
+  pub fn new(x: i32, y: i32, color: color::AnsiValue, forces: &ForceFields) -> Self {
     Self {
+      t: 0,
       x,
       y,
-      t: 0,
+      since: 0,
+      home: (x, y),
       color,
       state: PeachState::Idle,
-      radiate: None,
+      forces: forces.clone(),
+      debris: ParticleSystem::new((x, y), color, DEBRIS_Z_IDX, forces)
+        .with_physics(DEBRIS_MATERIAL),
     }
   }
 
-  pub fn exploded(&self) -> bool {
-    matches!(
-      self.state,
-      PeachState::Explode {
-        t: _,
-        target_letters: _,
-        rand_letters: _
+  /// Where the peach is, after any force fields have moved it.
+  fn pos(&self) -> (i32, i32) {
+    match &self.state {
+      PeachState::Idle => self.forces.displace(self.since, self.t, (self.x, self.y)),
+      PeachState::Thrown(body) => {
+        let (x, y) = body.pos();
+        (x - 1, y - 1)
       }
-    )
+      PeachState::Held { .. } | PeachState::Explode => (self.x, self.y),
+    }
+  }
+
+  pub fn exploded(&self) -> bool {
+    matches!(self.state, PeachState::Explode)
+  }
+
+  /// Moves the letters of the greeting the peach burst into.
+  pub fn move_letters(&mut self, relayout: &Relayout) {
+    self.debris.move_letters(relayout);
   }
 
   pub fn explode<R: Rng>(&mut self, target_letters: Vec<(char, (i32, i32))>, rng: &mut R) {
-    const RADIUS: i32 = 70;
-    self.state = PeachState::Explode {
-      t: self.t,
-      target_letters,
-      rand_letters: (0..200)
-        .map(|_| {
-          let mut dx = rng.gen_range(-RADIUS..=RADIUS);
-          let mut dy = rng.gen_range(-RADIUS..=RADIUS);
-          while dx * dx + dy * dy > (RADIUS * RADIUS) || self.x + dx < 0 || self.y + dy < 0 {
-            dx = rng.gen_range(-RADIUS..=RADIUS);
-            dy = rng.gen_range(-RADIUS..=RADIUS);
-          }
-
-          let letter = rng.gen_range('a'..='z');
-          (letter, (self.x + dx, self.y + dy))
-        })
-        .collect(),
+    const EMITTER: Emitter = Emitter {
+      count: 200,
+      radius: 70,
+      glyphs: 'a'..='z',
     };
+    self.debris.set_origin(self.pos());
+    self.state = PeachState::Explode;
+    self.debris.emit_targeted(target_letters);
+    self.debris.burst(&EMITTER, rng);
   }
 
-  pub fn hitbox(&self) -> (i32, i32) {
-    (self.x + 1, self.y + 1)
+  /// Moves the peach by `(dx, dy)` if it is sitting still, and where it goes
+  /// back to either way.
+  pub fn shift(&mut self, (dx, dy): (i32, i32)) {
+    self.home = (self.home.0 + dx, self.home.1 + dy);
+    if let PeachState::Idle = self.state {
+      self.x += dx;
+      self.y += dy;
+    }
   }
 
-  pub fn radiate(&mut self, pos: (i32, i32)) {
-    self.radiate = Some(Radiate { t: self.t, pos });
+  /// Comes to rest wherever a thrown peach has landed.
+  fn land(&mut self, (x, y): (i32, i32)) {
+    (self.x, self.y) = (x, y);
+    self.since = self.t;
+    self.state = PeachState::Idle;
   }
 
-  fn debris_pos(
-    &self,
-    (t, targeted, _, (x, y)): &(usize, bool, char, (i32, i32)),
-  ) -> ((i32, i32), bool) {
-    let mut pos = explosion_path((self.t - t) as f32, (*x, *y), (self.x, self.y));
-    let resting = pos == (*x, *y);
-    if !targeted {
-      pos = move_per_radiate(&self.radiate, self.t, pos);
+  pub fn hitbox(&self) -> (i32, i32) {
+    let (x, y) = self.pos();
+    (x + 1, y + 1)
+  }
+
+  /// Moves a thrown peach through `solids`, and its debris through
+  /// `debris_solids`.
+  pub fn simulate(&mut self, solids: &Solids, debris_solids: &Solids) {
+    if let PeachState::Thrown(body) = &mut self.state {
+      body.step(&MATERIAL, solids);
     }
-    (pos, resting)
+    self.debris.simulate(debris_solids);
   }
 
+  /// Splashes a thrown peach that has come down in the water, and puts it
+  /// back in the basket.
   pub fn maybe_dunk(&mut self, water: &mut Water) {
-    if let PeachState::Explode {
-      t,
-      target_letters,
-      rand_letters,
-    } = self.state.clone()
-    {
-      self.state = PeachState::Explode {
-        t,
-        target_letters,
-        rand_letters: rand_letters
-          .clone()
-          .into_iter()
-          .filter(|&(c, pos)| {
-            let (pos, resting) = self.debris_pos(&(t, false, c, pos));
-            if resting && water.is_wet(pos) {
-              water.click(pos.0 as u32, pos.1 as u32);
-              false
-            } else {
-              true
-            }
-          })
-          .collect(),
-      };
+    if let PeachState::Thrown(body) = &self.state {
+      let pos = body.pos();
+      if body.grounded() && water.is_wet(pos) {
+        water.click(pos.0 as u32, pos.1 as u32);
+        self.land(self.home);
+      }
     }
+    self.debris.maybe_dunk(water);
   }
 }
 
 impl Entity for Peach {
   fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (crate::util::Draw, (i32, i32))> + '_> {
+    let (x, y) = self.pos();
     match &self.state {
-      PeachState::Idle | PeachState::Held { dx: _, dy: _ } => Box::new(
+      PeachState::Idle | PeachState::Held { .. } | PeachState::Thrown(_) => Box::new(
         [
           (
             Draw::new(',')
               .with_fg(color::AnsiValue::rgb(2, 1, 0))
               .with_z(Z_IDX),
-            (self.x + 1, self.y),
-          ),
-          (
-            Draw::new('(').with_fg(self.color).with_z(Z_IDX),
-            (self.x, self.y + 1),
+            (x + 1, y),
           ),
+          (Draw::new('(').with_fg(self.color).with_z(Z_IDX), (x, y + 1)),
           (
             Draw::new('@').with_fg(self.color).with_z(Z_IDX),
-            (self.x + 1, self.y + 1),
+            (x + 1, y + 1),
           ),
         ]
         .into_iter(),
       ),
-      PeachState::Explode {
-        t,
-        target_letters,
-        rand_letters,
-      } => Box::new(
-        (target_letters
-          .iter()
-          .zip(iter::repeat(true))
-          .chain(rand_letters.iter().zip(iter::repeat(false))))
-        .map(move |((c, (x, y)), targeted)| {
-          let (pos, _) = self.debris_pos(&(*t, targeted, *c, (*x, *y)));
-          (
-            Draw::new(*c)
-              .with_fg(self.color)
-              .with_z(DEBRIS_Z_IDX + if targeted { 1 } else { 0 }),
-            pos,
-          )
-        }),
-      ),
+      PeachState::Explode => self.debris.iterate_tiles(),
     }
   }
 
   fn tick(&mut self, t: usize) {
     self.t = t;
+    if let PeachState::Thrown(body) = &self.state {
+      if body.resting() {
+        self.land(self.pos());
+      }
+    }
+    self.debris.tick(t);
+  }
+
+  /// A held peach follows the mouse wherever it goes.
+  fn bounds(&self) -> Option<Bounds> {
+    match self.state {
+      PeachState::Idle => Some(Bounds::rect(self.pos(), (2, 2), Z_IDX)),
+      PeachState::Held { .. } => Some(Bounds::everywhere(Z_IDX)),
+      PeachState::Thrown(_) | PeachState::Explode => None,
+    }
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    let dx = self.x - x as i32;
-    let dy = self.y - y as i32;
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    let pos = self.pos();
+    let dx = pos.0 - x as i32;
+    let dy = pos.1 - y as i32;
     if (-1..=0).contains(&dx)
       && (-1..=0).contains(&dy)
       && (dx != 0 || dy != 0)
-      && self.state == PeachState::Idle
+      && matches!(self.state, PeachState::Idle)
     {
-      self.state = PeachState::Held { dx, dy };
+      (self.x, self.y) = pos;
+      self.state = PeachState::Held {
+        dx,
+        dy,
+        drags: Vec::new(),
+      };
+      true
+    } else {
+      false
     }
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
-    match self.state {
-      PeachState::Held { dx, dy } => {
-        self.x = x as i32 + dx;
-        self.y = y as i32 + dy;
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    match &mut self.state {
+      PeachState::Held { dx, dy, drags } => {
+        self.x = x as i32 + *dx;
+        self.y = y as i32 + *dy;
+        drags.retain(|&(t, _)| t + THROW_TICKS > self.t);
+        drags.push((self.t, (self.x, self.y)));
+        true
       }
-      PeachState::Idle
-      | PeachState::Explode {
-        t: _,
-        target_letters: _,
-        rand_letters: _,
-      } => {}
+      PeachState::Idle | PeachState::Thrown(_) | PeachState::Explode => false,
     }
   }
 
-  fn release(&mut self, _x: u32, _y: u32) {
-    match self.state {
-      PeachState::Held { dx: _, dy: _ } => {
-        self.state = PeachState::Idle;
+  /// Throws the peach as fast as it was last dragged, tossing it up higher
+  /// the faster it goes.
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    let PeachState::Held { drags, .. } = &self.state else {
+      return false;
+    };
+    let recent: Vec<_> = drags
+      .iter()
+      .filter(|&&(t, _)| t + THROW_TICKS > self.t)
+      .collect();
+    let vel = match (recent.first(), recent.last()) {
+      // A peach dragged back to where it was is dropped, not thrown.
+      (Some(&&(t0, from)), Some(&&(t1, to))) if t1 > t0 && from != to => {
+        let dt = (t1 - t0) as f32;
+        let vel = ((to.0 - from.0) as f32 / dt, (to.1 - from.1) as f32 / dt);
+        let speed = vel.0.hypot(vel.1);
+        let scale = MAX_THROW_SPEED.min(speed) / speed;
+        (vel.0 * scale, vel.1 * scale)
       }
-      PeachState::Idle
-      | PeachState::Explode {
-        t: _,
-        target_letters: _,
-        rand_letters: _,
-      } => {}
-    }
+      _ => (0., 0.),
+    };
+    let climb = THROW_LIFT * vel.0.hypot(vel.1);
+    self.state = PeachState::Thrown(Body::new((self.x + 1, self.y + 1), vel, climb));
+    true
   }
+
+  /// Arrow keys nudge a held peach over by a tile, keeping it the same
+  /// distance from the mouse.
+  fn key(&mut self, key: Key) -> bool {
+    let PeachState::Held { dx, dy, .. } = &mut self.state else {
+      return false;
+    };
+    let (nudge_x, nudge_y) = match key {
+      Key::Left => (-1, 0),
+      Key::Right => (1, 0),
+      Key::Up => (0, -1),
+      Key::Down => (0, 1),
+      _ => return false,
+    };
+    *dx += nudge_x;
+    *dy += nudge_y;
+    self.x += nudge_x;
+    self.y += nudge_y;
+    true
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, _tuning: &Tuning) {}
 }
@@ -0,0 +1,192 @@
+use crate::util::G;
+
+/// How high above the ground a body has to be to fly over solid tiles.
+const WALL_HEIGHT: f32 = 1.;
+/// Bodies landing slower than this stop bouncing, and bodies sliding slower
+/// than this come to rest.
+const REST_CLIMB: f32 = 0.3;
+const REST_SPEED: f32 = 0.1;
+
+/// How bodies bounce off of the ground and walls, and slide along the ground.
+#[derive(Clone, Copy)]
+pub struct Material {
+  /// The fraction of its speed a body keeps when it bounces.
+  pub restitution: f32,
+  /// The fraction of its speed a body loses each tick it touches the ground.
+  pub friction: f32,
+}
+
+/// Which tiles of the window bodies can't move through. Everything off of the
+/// window is solid, however high up it is.
+#[derive(Clone)]
+pub struct Solids {
+  width: u32,
+  height: u32,
+  solid: Vec<bool>,
+}
+
+impl Solids {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self {
+      width,
+      height,
+      solid: vec![false; (width * height) as usize],
+    }
+  }
+
+  fn idx(&self, (x, y): (i32, i32)) -> Option<usize> {
+    if (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+      Some((x as u32 + y as u32 * self.width) as usize)
+    } else {
+      None
+    }
+  }
+
+  pub fn fill(&mut self, pos: (i32, i32)) {
+    if let Some(idx) = self.idx(pos) {
+      self.solid[idx] = true;
+    }
+  }
+
+  pub fn is_solid(&self, pos: (i32, i32)) -> bool {
+    self.idx(pos).is_none_or(|idx| self.solid[idx])
+  }
+
+  /// Whether a body `low` enough to hit walls can't move into `pos`.
+  fn blocks(&self, pos: (i32, i32), low: bool) -> bool {
+    match self.idx(pos) {
+      Some(idx) => low && self.solid[idx],
+      None => true,
+    }
+  }
+}
+
+/// Something flying around under gravity, seen from the side and above: where
+/// it is over the ground, and how high above it it is.
+pub struct Body {
+  pos: (f32, f32),
+  height: f32,
+  /// How far the body moves over the ground, and up, each tick.
+  vel: (f32, f32),
+  climb: f32,
+  resting: bool,
+}
+
+impl Body {
+  /// A body on the ground at `pos`, heading off at `vel` and `climb`.
+  pub fn new(pos: (i32, i32), vel: (f32, f32), climb: f32) -> Self {
+    Self {
+      pos: (pos.0 as f32, pos.1 as f32),
+      height: 0.,
+      vel,
+      climb,
+      resting: false,
+    }
+  }
+
+  /// A body flung up from `origin` that would land on `target` after as long
+  /// as `explosion_path` takes to get there.
+  pub fn launch(origin: (i32, i32), target: (i32, i32)) -> Self {
+    let dx = (target.0 - origin.0) as f32;
+    let dy = (target.1 - origin.1) as f32;
+    let target_t = dx.abs() * 0.3 + dy.abs() * 0.4 + 2.;
+    Self::new(origin, (dx / target_t, dy / target_t), -G / 2. * target_t)
+  }
+
+  /// Where the body is drawn.
+  pub fn pos(&self) -> (i32, i32) {
+    (
+      self.pos.0.round() as i32,
+      (self.pos.1 - self.height).round() as i32,
+    )
+  }
+
+  /// The tile of the ground the body is over.
+  fn tile(&self) -> (i32, i32) {
+    (self.pos.0.round() as i32, self.pos.1.round() as i32)
+  }
+
+  pub fn grounded(&self) -> bool {
+    self.height <= 0.
+  }
+
+  pub fn resting(&self) -> bool {
+    self.resting
+  }
+
+  /// Moves the body by `offset` without changing how it is flying, knocking it
+  /// loose if it was resting.
+  pub fn push(&mut self, offset: (i32, i32)) {
+    if offset != (0, 0) {
+      self.pos.0 += offset.0 as f32;
+      self.pos.1 += offset.1 as f32;
+      self.resting = false;
+    }
+  }
+
+  /// Moves the body on by a tick, bouncing off of the ground and out of the
+  /// way of solid tiles.
+  pub fn step(&mut self, material: &Material, solids: &Solids) {
+    if self.resting {
+      return;
+    }
+
+    self.height += self.climb + G / 2.;
+    self.climb += G;
+    if self.height <= 0. {
+      self.height = 0.;
+      self.climb = if self.climb.abs() < REST_CLIMB {
+        0.
+      } else {
+        -self.climb * material.restitution
+      };
+      self.vel.0 *= 1. - material.friction;
+      self.vel.1 *= 1. - material.friction;
+    }
+
+    // Walls only stop bodies low enough to hit them, and only from outside,
+    // so bodies flung from inside of something can get out. The edges of the
+    // window stop everything.
+    let from = self.tile();
+    let low = self.height < WALL_HEIGHT && !solids.is_solid(from);
+    let x = self.pos.0 + self.vel.0;
+    if solids.blocks((x.round() as i32, from.1), low) {
+      self.vel.0 = -self.vel.0 * material.restitution;
+    } else {
+      self.pos.0 = x;
+    }
+    let y = self.pos.1 + self.vel.1;
+    if solids.blocks((self.tile().0, y.round() as i32), low) {
+      self.vel.1 = -self.vel.1 * material.restitution;
+    } else {
+      self.pos.1 = y;
+    }
+
+    if self.grounded() && self.climb == 0. && self.vel.0.hypot(self.vel.1) < REST_SPEED {
+      self.settle(solids);
+    }
+  }
+
+  /// Brings the body to rest on its tile, or tumbles it off onto a free one
+  /// next to it if something is already there.
+  fn settle(&mut self, solids: &Solids) {
+    let (x, y) = self.tile();
+    let tile = [
+      (0, 0),
+      (-1, 0),
+      (1, 0),
+      (0, 1),
+      (-1, 1),
+      (1, 1),
+      (0, -1),
+      (-1, -1),
+      (1, -1),
+    ]
+    .into_iter()
+    .map(|(dx, dy)| (x + dx, y + dy))
+    .find(|&tile| !solids.is_solid(tile))
+    .unwrap_or((x, y));
+    self.pos = (tile.0 as f32, tile.1 as f32);
+    self.resting = true;
+  }
+}
@@ -0,0 +1,390 @@
+use std::{
+  collections::VecDeque,
+  fs,
+  io::{self, Write},
+  path::Path,
+};
+
+use termion::event::{Key, MouseButton, MouseEvent};
+
+use crate::tuning::Tuning;
+
+/// An input that affects the run, as seen by the main loop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Input {
+  /// A mouse event, in the terminal's 1-based coordinates.
+  Mouse(MouseEvent),
+  Key(Key),
+  Resize(u32, u32),
+  /// The tuning file was reloaded.
+  Retune(Tuning),
+  Quit,
+}
+
+/// Writes every input of a run, along with the tick it was handled on, so the
+/// run can be reproduced exactly with `Replay`.
+///
+/// The format is line based. A header gives the seed, window size and any
+/// options that change what is played, and each following line is
+/// `<tick> <input> <args...>`:
+///
+/// ```text
+/// seed 27418995609531717
+/// size 120 40
+/// option --story stories/birthday.story
+/// 12 press left 53 12
+/// 13 hold 54 12
+/// 14 release 54 12
+/// 40 key space
+/// 80 resize 100 30
+/// 90 tune basket = 9 10
+/// 90 tune hole = 102 12
+/// 95 quit
+/// ```
+pub struct InputRecorder<W: Write> {
+  out: W,
+}
+
+impl<W: Write> InputRecorder<W> {
+  /// `options` are the flags and values the run was started with that change
+  /// what is played, which have to be given again to replay it.
+  pub fn new(
+    mut out: W,
+    seed: u64,
+    (width, height): (u32, u32),
+    options: &[(&str, String)],
+  ) -> io::Result<Self> {
+    writeln!(out, "seed {seed}")?;
+    writeln!(out, "size {width} {height}")?;
+    for (flag, value) in options {
+      writeln!(out, "option {flag} {}", escape(value))?;
+    }
+    Ok(Self { out })
+  }
+
+  pub fn record(&mut self, t: usize, input: &Input) -> io::Result<()> {
+    match input {
+      Input::Mouse(MouseEvent::Press(button, x, y)) => {
+        writeln!(self.out, "{t} press {} {x} {y}", button_name(*button))
+      }
+      Input::Mouse(MouseEvent::Hold(x, y)) => writeln!(self.out, "{t} hold {x} {y}"),
+      Input::Mouse(MouseEvent::Release(x, y)) => writeln!(self.out, "{t} release {x} {y}"),
+      Input::Key(key) => {
+        let name = key_name(*key).expect("Only named keys are recorded");
+        writeln!(self.out, "{t} key {name}")
+      }
+      Input::Resize(width, height) => writeln!(self.out, "{t} resize {width} {height}"),
+      Input::Retune(tuning) => tuning
+        .lines()
+        .iter()
+        .try_for_each(|line| writeln!(self.out, "{t} tune {line}")),
+      Input::Quit => writeln!(self.out, "{t} quit"),
+    }
+  }
+
+  pub fn finish(&mut self) -> io::Result<()> {
+    self.out.flush()
+  }
+}
+
+/// The inputs of a recorded run, fed back on the ticks they were recorded on.
+pub struct Replay {
+  seed: u64,
+  size: (u32, u32),
+  options: Vec<(String, String)>,
+  inputs: VecDeque<(usize, Input)>,
+}
+
+impl Replay {
+  pub fn load(path: &Path) -> Result<Self, String> {
+    let text = fs::read_to_string(path)
+      .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Self::parse(&text).map_err(|err| format!("{}:{err}", path.display()))
+  }
+
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let mut seed = None;
+    let mut size = None;
+    let mut options = Vec::new();
+    let mut inputs = VecDeque::new();
+    // Each `tune` line sets one value, on top of the ones before it.
+    let mut tuning = Tuning::default();
+
+    for (line_idx, line) in text.lines().enumerate() {
+      let err = |msg: &str| format!("{}: {msg}: '{line}'", line_idx + 1);
+      let words: Vec<_> = line.split_whitespace().collect();
+      let num = |idx: usize| -> Result<u32, String> {
+        words
+          .get(idx)
+          .and_then(|word| word.parse().ok())
+          .ok_or_else(|| err("Expected a number"))
+      };
+
+      match words.as_slice() {
+        [] => {}
+        [comment, ..] if comment.starts_with('#') => {}
+        ["seed", value] => {
+          seed = Some(value.parse().map_err(|_| err("Invalid seed"))?);
+        }
+        ["size", _, _] => size = Some((num(1)?, num(2)?)),
+        ["option", flag, ..] => {
+          let value = line.trim_start()["option".len()..]
+            .trim_start()
+            .strip_prefix(flag)
+            .and_then(|value| value.strip_prefix(' '))
+            .ok_or_else(|| err("Expected option <flag> <value>"))?;
+          options.push((flag.to_string(), unescape(value)));
+        }
+        [tick, kind, ..] => {
+          let tick: usize = tick.parse().map_err(|_| err("Invalid tick"))?;
+          if inputs.back().is_some_and(|&(prev, _)| prev > tick) {
+            return Err(err("Ticks must not decrease"));
+          }
+          // Terminal coordinates start at 1.
+          let coord = |idx: usize| match num(idx)? {
+            n @ 1..=0xffff => Ok(n as u16),
+            _ => Err(err("Coordinates must be between 1 and 65535")),
+          };
+          let input = match (*kind, words.len()) {
+            ("press", 5) => {
+              let button = parse_button(words[2]).ok_or_else(|| err("Unknown mouse button"))?;
+              Input::Mouse(MouseEvent::Press(button, coord(3)?, coord(4)?))
+            }
+            ("hold", 4) => Input::Mouse(MouseEvent::Hold(coord(2)?, coord(3)?)),
+            ("release", 4) => Input::Mouse(MouseEvent::Release(coord(2)?, coord(3)?)),
+            ("key", 3) => Input::Key(parse_key(words[2]).ok_or_else(|| err("Unknown key"))?),
+            ("resize", 4) => Input::Resize(num(2)?, num(3)?),
+            ("tune", _) => {
+              let (_, setting) = line.split_once("tune").unwrap();
+              tuning.set(setting).map_err(|msg| err(&msg))?;
+              // A reload sets every value at once, so apply them together.
+              if matches!(inputs.back(), Some(&(prev, Input::Retune(_))) if prev == tick) {
+                inputs.pop_back();
+              }
+              Input::Retune(tuning)
+            }
+            ("quit", 2) => Input::Quit,
+            _ => return Err(err("Unknown input")),
+          };
+          inputs.push_back((tick, input));
+        }
+        _ => return Err(err("Unknown line")),
+      }
+    }
+
+    Ok(Self {
+      seed: seed.ok_or("Missing 'seed' line")?,
+      size: size.ok_or("Missing 'size' line")?,
+      options,
+      inputs,
+    })
+  }
+
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  pub fn size(&self) -> (u32, u32) {
+    self.size
+  }
+
+  /// Checks that the run is started with the same `options` the replay was
+  /// recorded with, since it would play out differently otherwise.
+  pub fn check_options(&self, options: &[(&str, String)]) -> Result<(), String> {
+    let show = |options: Vec<(&str, &str)>| match options.as_slice() {
+      [] => "no options".to_string(),
+      options => options
+        .iter()
+        .map(|(flag, value)| format!("{flag} {value:?}"))
+        .collect::<Vec<_>>()
+        .join(" "),
+    };
+    let recorded: Vec<_> = self
+      .options
+      .iter()
+      .map(|(flag, value)| (flag.as_str(), value.as_str()))
+      .collect();
+    let given: Vec<_> = options
+      .iter()
+      .map(|(flag, value)| (*flag, value.as_str()))
+      .collect();
+    if recorded == given {
+      Ok(())
+    } else {
+      Err(format!(
+        "The replay was recorded with {}, but was given {}",
+        show(recorded),
+        show(given)
+      ))
+    }
+  }
+
+  pub fn is_finished(&self) -> bool {
+    self.inputs.is_empty()
+  }
+
+  /// Removes and returns every input due on or before tick `t`.
+  pub fn inputs_until(&mut self, t: usize) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    while let Some(&(tick, input)) = self.inputs.front() {
+      if tick > t {
+        break;
+      }
+      inputs.push(input);
+      self.inputs.pop_front();
+    }
+    inputs
+  }
+}
+
+/// Keeps an option's value on one line.
+fn escape(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+  let mut unescaped = String::new();
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    match (c, chars.clone().next()) {
+      ('\\', Some('n')) => {
+        unescaped.push('\n');
+        chars.next();
+      }
+      ('\\', Some('\\')) => {
+        unescaped.push('\\');
+        chars.next();
+      }
+      _ => unescaped.push(c),
+    }
+  }
+  unescaped
+}
+
+fn button_name(button: MouseButton) -> &'static str {
+  match button {
+    MouseButton::Left => "left",
+    MouseButton::Right => "right",
+    MouseButton::Middle => "middle",
+    MouseButton::WheelUp => "wheel-up",
+    MouseButton::WheelDown => "wheel-down",
+  }
+}
+
+fn parse_button(name: &str) -> Option<MouseButton> {
+  Some(match name {
+    "left" => MouseButton::Left,
+    "right" => MouseButton::Right,
+    "middle" => MouseButton::Middle,
+    "wheel-up" => MouseButton::WheelUp,
+    "wheel-down" => MouseButton::WheelDown,
+    _ => return None,
+  })
+}
+
+/// The name of `key` in recordings, for the keys the animation reacts to.
+/// Keys that are written as themselves are printable and not whitespace.
+pub fn key_name(key: Key) -> Option<String> {
+  Some(match key {
+    Key::Char(' ') => "space".to_string(),
+    Key::Char('\n') => "enter".to_string(),
+    Key::Char(c) if !c.is_whitespace() && !c.is_control() => c.to_string(),
+    Key::Esc => "esc".to_string(),
+    Key::Up => "up".to_string(),
+    Key::Down => "down".to_string(),
+    Key::Left => "left".to_string(),
+    Key::Right => "right".to_string(),
+    _ => return None,
+  })
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+  Some(match name {
+    "space" => Key::Char(' '),
+    "enter" => Key::Char('\n'),
+    "esc" => Key::Esc,
+    "up" => Key::Up,
+    "down" => Key::Down,
+    "left" => Key::Left,
+    "right" => Key::Right,
+    _ => {
+      let mut chars = name.chars();
+      match (chars.next(), chars.next()) {
+        (Some(c), None) => Key::Char(c),
+        _ => return None,
+      }
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HEADER: &str = "seed 1\nsize 120 40\n";
+
+  #[test]
+  fn options_round_trip() {
+    let options = [
+      ("--message", "Two\nlines, with a \\n in them".to_string()),
+      ("--story", "my stories/a.story".to_string()),
+    ];
+    let mut out = Vec::new();
+    InputRecorder::new(&mut out, 1, (120, 40), &options).unwrap();
+    let replay = Replay::parse(&String::from_utf8(out).unwrap()).unwrap();
+    assert_eq!(replay.check_options(&options), Ok(()));
+
+    assert_eq!(
+      replay.check_options(&options[1..]),
+      Err(
+        "The replay was recorded with --message \"Two\\nlines, with a \\\\n in them\" \
+         --story \"my stories/a.story\", but was given --story \"my stories/a.story\""
+          .to_string()
+      )
+    );
+    let replay = Replay::parse(HEADER).unwrap();
+    assert_eq!(
+      replay.check_options(&options[1..]),
+      Err(
+        "The replay was recorded with no options, but was given --story \"my stories/a.story\""
+          .to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn retunes_round_trip() {
+    let tuning = Tuning {
+      basket: (-3, 7),
+      water_c: 0.35,
+      ..Tuning::default()
+    };
+    let mut out = Vec::new();
+    let mut recorder = InputRecorder::new(&mut out, 1, (120, 40), &[]).unwrap();
+    recorder
+      .record(4, &Input::Retune(Tuning::default()))
+      .unwrap();
+    recorder.record(9, &Input::Retune(tuning)).unwrap();
+
+    let mut replay = Replay::parse(&String::from_utf8(out).unwrap()).unwrap();
+    assert_eq!(
+      replay.inputs_until(9),
+      [Input::Retune(Tuning::default()), Input::Retune(tuning)]
+    );
+    assert!(Replay::parse(&format!("{HEADER}1 tune step_period = 0\n")).is_err());
+  }
+
+  #[test]
+  fn coordinates_start_at_1() {
+    assert!(Replay::parse(&format!("{HEADER}1 press left 1 1\n")).is_ok());
+    for input in ["press left 0 3", "hold 3 0", "release 0 0", "hold 70000 1"] {
+      let err = Replay::parse(&format!("{HEADER}1 {input}\n"))
+        .err()
+        .unwrap();
+      assert_eq!(
+        err,
+        format!("3: Coordinates must be between 1 and 65535: '1 {input}'")
+      );
+    }
+  }
+}
@@ -0,0 +1,56 @@
+use std::{
+  backtrace::Backtrace,
+  fs,
+  io::{self, Stdout, Write},
+  panic,
+  path::PathBuf,
+  sync::{Mutex, PoisonError},
+};
+
+use termion::{
+  cursor,
+  raw::{IntoRawMode, RawTerminal},
+  screen::ToMainScreen,
+  style,
+};
+
+/// Undoes `MouseTerminal`, which can't be reached from the panic hook.
+const DISABLE_MOUSE: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+/// Holds the terminal in raw mode. This lives here instead of in the window so
+/// the panic hook can take the terminal back out of raw mode.
+static RAW: Mutex<Option<RawTerminal<Stdout>>> = Mutex::new(None);
+
+/// Puts the terminal in raw mode, and installs a panic hook which restores the
+/// terminal before reporting the panic, both on stderr and in `crash_file`.
+pub fn install(crash_file: PathBuf) -> io::Result<()> {
+  *RAW.lock().unwrap() = Some(io::stdout().into_raw_mode()?);
+
+  let report = panic::take_hook();
+  panic::set_hook(Box::new(move |info| {
+    restore();
+    report(info);
+    let crash = format!("{info}\n\n{}", Backtrace::force_capture());
+    if fs::write(&crash_file, crash).is_ok() {
+      eprintln!("Crash report written to {}", crash_file.display());
+    }
+  }));
+  Ok(())
+}
+
+/// Leaves raw mode, stops mouse reporting, shows the cursor and switches back
+/// from the alternate screen, which brings back what was on the terminal
+/// before. Does nothing to the terminal mode if `install` was never called.
+pub fn restore() {
+  drop(RAW.lock().unwrap_or_else(PoisonError::into_inner).take());
+
+  let mut stdout = io::stdout();
+  let _ = write!(
+    stdout,
+    "{DISABLE_MOUSE}{}{}{}",
+    style::Reset,
+    ToMainScreen,
+    cursor::Show
+  );
+  let _ = stdout.flush();
+}
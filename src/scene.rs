@@ -1,17 +1,37 @@
-use crate::entity::Entity;
+use termion::event::Key;
+
+use crate::{
+  entity::{dispatch, Bounds, Entity},
+  tuning::Tuning,
+};
 
 pub struct Scene<'a> {
   entities: Vec<Box<dyn Entity + 'a>>,
+  /// While paused, entities aren't ticked and ignore input. Entities are
+  /// ticked with the time less however long the scene has been paused for.
+  paused: bool,
+  paused_ticks: usize,
 }
 
 impl<'a> Scene<'a> {
   pub fn new() -> Self {
-    Self { entities: vec![] }
+    Self {
+      entities: vec![],
+      paused: false,
+      paused_ticks: 0,
+    }
   }
 
   pub fn add_entity<E: Entity + 'a>(&mut self, entity: Box<E>) {
     self.entities.push(entity);
   }
+
+  fn entities_mut(&mut self) -> impl Iterator<Item = &mut dyn Entity> + use<'_, 'a> {
+    self
+      .entities
+      .iter_mut()
+      .map(|entity| entity.as_mut() as &mut dyn Entity)
+  }
 }
 
 impl<'a> Entity for Scene<'a> {
@@ -25,27 +45,54 @@ impl<'a> Entity for Scene<'a> {
   }
 
   fn tick(&mut self, t: usize) {
+    if self.paused {
+      self.paused_ticks += 1;
+      return;
+    }
+    let t = t - self.paused_ticks;
     self.entities.iter_mut().for_each(|entity| entity.tick(t));
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    self
-      .entities
-      .iter_mut()
-      .for_each(|entity| entity.click(x, y));
+  fn bounds(&self) -> Option<Bounds> {
+    Bounds::enclosing(self.entities.iter().map(|entity| entity.bounds()))
+  }
+
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    !self.paused && dispatch(self.entities_mut(), (x, y), |entity| entity.click(x, y)).is_some()
+  }
+
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    !self.paused && dispatch(self.entities_mut(), (x, y), |entity| entity.drag(x, y)).is_some()
+  }
+
+  fn release(&mut self, x: u32, y: u32) -> bool {
+    !self.paused && dispatch(self.entities_mut(), (x, y), |entity| entity.release(x, y)).is_some()
+  }
+
+  /// Esc pauses and unpauses. Other keys go to each entity in order until one
+  /// consumes it.
+  fn key(&mut self, key: Key) -> bool {
+    if key == Key::Esc {
+      self.paused = !self.paused;
+      return true;
+    }
+    if self.paused {
+      return false;
+    }
+    self.entities.iter_mut().any(|entity| entity.key(key))
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
+  fn resize(&mut self, width: u32, height: u32) {
     self
       .entities
       .iter_mut()
-      .for_each(|entity| entity.drag(x, y));
+      .for_each(|entity| entity.resize(width, height));
   }
 
-  fn release(&mut self, x: u32, y: u32) {
+  fn retune(&mut self, tuning: &Tuning) {
     self
       .entities
       .iter_mut()
-      .for_each(|entity| entity.release(x, y));
+      .for_each(|entity| entity.retune(tuning));
   }
 }
@@ -0,0 +1,159 @@
+use crate::{color::Rgb, util::Draw};
+
+/// Ascii art that can be drawn anywhere. Each cell has a character, whether it
+/// is drawn over what's behind it, and optionally a color.
+#[derive(Clone)]
+pub struct Sprite {
+  width: usize,
+  height: usize,
+  chars: Vec<char>,
+  opaque: Vec<bool>,
+  colors: Vec<Option<Rgb>>,
+  z_idx: i32,
+}
+
+impl Sprite {
+  /// Makes a sprite from rows of art, where spaces are see-through.
+  pub fn new(rows: &[&str]) -> Self {
+    let width = rows
+      .iter()
+      .map(|row| row.chars().count())
+      .max()
+      .unwrap_or(0);
+    let chars: Vec<_> = rows
+      .iter()
+      .flat_map(|row| row.chars().chain(std::iter::repeat(' ')).take(width))
+      .collect();
+    Self {
+      width,
+      height: rows.len(),
+      opaque: chars.iter().map(|&c| c != ' ').collect(),
+      colors: vec![None; chars.len()],
+      chars,
+      z_idx: 0,
+    }
+  }
+
+  /// Pads the sprite with see-through columns on the right, up to `width`.
+  pub fn with_width(self, width: usize) -> Self {
+    if width <= self.width {
+      return self;
+    }
+    Self {
+      width,
+      chars: pad_rows(&self.chars, self.height, self.width, width, ' '),
+      opaque: pad_rows(&self.opaque, self.height, self.width, width, false),
+      colors: pad_rows(&self.colors, self.height, self.width, width, None),
+      ..self
+    }
+  }
+
+  /// Draws only the cells marked by a non-space in `mask`, spaces included.
+  pub fn with_mask(self, mask: &[&str]) -> Self {
+    let mut opaque = vec![false; self.chars.len()];
+    for (y, row) in mask.iter().enumerate().take(self.height) {
+      for (x, c) in row.chars().enumerate().take(self.width) {
+        opaque[y * self.width + x] = c != ' ';
+      }
+    }
+    Self { opaque, ..self }
+  }
+
+  pub fn with_fg<C: Into<Rgb>>(self, color: C) -> Self {
+    Self {
+      colors: vec![Some(color.into()); self.chars.len()],
+      ..self
+    }
+  }
+
+  /// Colors each cell by looking up the character at the same place in
+  /// `layer` in `palette`. Cells with no color in the palette are left as
+  /// they were.
+  pub fn with_colors<C: Into<Rgb> + Copy>(mut self, layer: &[&str], palette: &[(char, C)]) -> Self {
+    for (y, row) in layer.iter().enumerate().take(self.height) {
+      for (x, c) in row.chars().enumerate().take(self.width) {
+        if let Some(&(_, color)) = palette.iter().find(|&&(key, _)| key == c) {
+          self.colors[y * self.width + x] = Some(color.into());
+        }
+      }
+    }
+    self
+  }
+
+  pub fn with_z(self, z_idx: i32) -> Self {
+    Self { z_idx, ..self }
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  /// The sprite flipped left to right, with characters that have a direction
+  /// turned around too.
+  pub fn mirrored(&self) -> Self {
+    Self {
+      chars: mirror_rows(&self.chars, self.width)
+        .into_iter()
+        .map(mirror_char)
+        .collect(),
+      opaque: mirror_rows(&self.opaque, self.width),
+      colors: mirror_rows(&self.colors, self.width),
+      ..*self
+    }
+  }
+
+  /// The tiles of the sprite with its top left corner at `pos`.
+  pub fn draw(&self, pos: (i32, i32)) -> impl Iterator<Item = (Draw, (i32, i32))> + '_ {
+    (0..self.chars.len())
+      .filter(|&idx| self.opaque[idx])
+      .map(move |idx| {
+        let draw = Draw::new(self.chars[idx]).with_z(self.z_idx);
+        let draw = match self.colors[idx] {
+          Some(color) => draw.with_fg(color),
+          None => draw,
+        };
+        let x = pos.0 + (idx % self.width) as i32;
+        let y = pos.1 + (idx / self.width) as i32;
+        (draw, (x, y))
+      })
+  }
+}
+
+fn mirror_char(c: char) -> char {
+  match c {
+    '(' => ')',
+    ')' => '(',
+    '/' => '\\',
+    '\\' => '/',
+    '<' => '>',
+    '>' => '<',
+    _ => c,
+  }
+}
+
+fn pad_rows<T: Copy>(
+  cells: &[T],
+  height: usize,
+  width: usize,
+  new_width: usize,
+  fill: T,
+) -> Vec<T> {
+  (0..height)
+    .flat_map(|y| {
+      (0..new_width).map(move |x| {
+        if x < width {
+          cells[y * width + x]
+        } else {
+          fill
+        }
+      })
+    })
+    .collect()
+}
+
+fn mirror_rows<T: Copy>(cells: &[T], width: usize) -> Vec<T> {
+  cells
+    .chunks(width.max(1))
+    .flat_map(|row| row.iter().rev().copied())
+    .collect()
+}
@@ -0,0 +1,498 @@
+//! Story scripts, which drive what the bunny says and does.
+//!
+//! A script is a list of commands, one per line. Blank lines and lines
+//! starting with `#` are ignored, and a line `name:` labels the command after
+//! it, so it can be jumped to. The commands are:
+//!
+//! - `pose sleep|wake|stand|step|blink|kazoo|blow|munch`
+//! - `face left|right`
+//! - `say <dx> [left] [bold] "<text>"`: show a dialog box `dx` columns from
+//!   the bunny, opening to the left if `left` is given.
+//! - `hide`: hide the dialog box.
+//! - `wait <ticks>`: wait until `ticks` after the last blocking command
+//!   finished.
+//! - `wait click [[outside] bunny|basket|hole|carrot]`: wait for a click, optionally
+//!   on (or outside of) something, or for Space or Enter.
+//! - `choose <target>:<label>...`: wait for a click on one of the targets, or
+//!   the number key of its place in the list, and jump to its label.
+//! - `blink on|off`: whether clicking the bunny makes it blink.
+//! - `walk [bunny|basket|hole|carrot] <x> <y>`: walk over to `(x, y)`, counted
+//!   from the top left corner of the bunny, basket, hole or carrot if one is
+//!   given. If
+//!   there is no way there, the bunny stays where it is.
+//! - `goto <label>`
+//! - `if completed <n> goto <label>`: jump if `n` activities are complete.
+//! - `complete`: mark an activity as complete.
+//! - `train freeze|unfreeze`
+//! - `peaches`: wait until every peach has been smashed by the train.
+//! - `dig`: fill the hole with dirt, and wait until it has been dug out.
+//! - `kazoo <x> <y>`: move the kazoo flung out of the hole.
+//! - `shreek <x> <y>`: send a shockwave out from `(x, y)`.
+//! - `shockwave <x> <y> <radius> <ticks>`: send a shockwave out from `(x, y)`
+//!   that slows down as it spreads, reaching `radius` after `ticks`.
+//! - `push|pull <x> <y> <radius> <distance> <ticks>`: push things within
+//!   `radius` of `(x, y)` away from it, or pull them in, by up to `distance`.
+//! - `vortex <x> <y> <radius> <degrees> <ticks>`: swirl things within `radius`
+//!   of `(x, y)` around it by up to `degrees` clockwise.
+//! - `wind <dx> <dy> <ticks>`: blow everything over by `(dx, dy)`.
+//! - `carrot appear|flip|behead|reveal`, `carrot move [<target>] <x> <y>`
+//! - `munch <delay> <period>`: keep eating the carrot, starting after `delay`
+//!   ticks and taking a bite every `period`. Replaces an earlier `munch` that
+//!   hasn't started yet.
+//! - `zoom <x> <y> <radius>`, `zoom2 <x> <y> <radius>`, `zoom out`
+//! - `zoom greeting`: close in on the greeting, wherever it was laid out.
+//! - `end`: finish the animation.
+//! - `after <ticks> <command>`: run `command` `ticks` after now, without
+//!   waiting for it. Only commands that run right away can be delayed.
+//!
+//! Waits, walks, clicks, `choose`, `peaches` and `dig` block until they are
+//! done. Every other command runs right away, so every loop has to block
+//! somewhere.
+
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use crate::{bunny::Direction, force::Force, tween::Easing};
+
+pub const DEFAULT_STORY: &str = include_str!("../stories/birthday.story");
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pose {
+  Sleep,
+  Wake,
+  Stand,
+  Step,
+  Blink,
+  Kazoo,
+  Blow,
+  Munch,
+}
+
+/// Something in the scene that can be clicked on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+  Bunny,
+  Basket,
+  Hole,
+  Carrot,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClickFilter {
+  Any,
+  On(Target),
+  Outside(Target),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CarrotAction {
+  Appear,
+  Flip,
+  Behead,
+  Reveal,
+  /// Move to an offset from the top left corner of a target, or from the
+  /// origin.
+  Move(Option<Target>, (i32, i32)),
+}
+
+/// A command of a story. Jumps hold the index of the command to jump to.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+  Pose(Pose),
+  Face(Direction),
+  Say {
+    dx: i32,
+    to_left: bool,
+    bold: bool,
+    text: String,
+  },
+  Hide,
+  Wait(usize),
+  WaitClick(ClickFilter),
+  Choose(Vec<(Target, usize)>),
+  Blink(bool),
+  /// Walk to an offset from the top left corner of a target, or from the
+  /// origin.
+  Walk(Option<Target>, (i32, i32)),
+  Goto(usize),
+  IfCompleted(u32, usize),
+  Complete,
+  Train {
+    frozen: bool,
+  },
+  Peaches,
+  Dig,
+  Kazoo((i32, i32)),
+  Shreek((i32, i32)),
+  Force(Force),
+  Carrot(CarrotAction),
+  Munch {
+    delay: usize,
+    period: usize,
+  },
+  Zoom((i32, i32), u32),
+  Zoom2((i32, i32), u32),
+  ZoomGreeting,
+  ZoomOut,
+  End,
+  After(usize, Box<Command>),
+}
+
+impl Command {
+  /// Whether the command reveals part of the greeting, apart from `peaches`,
+  /// which reveals part of it with each peach.
+  fn reveals(&self) -> bool {
+    match self {
+      Command::Dig | Command::Carrot(CarrotAction::Reveal) => true,
+      Command::After(_, command) => command.reveals(),
+      _ => false,
+    }
+  }
+
+  /// Whether the command neither blocks nor jumps.
+  fn is_instant(&self) -> bool {
+    !matches!(
+      self,
+      Command::Wait(_)
+        | Command::WaitClick(_)
+        | Command::Choose(_)
+        | Command::Walk(..)
+        | Command::Goto(_)
+        | Command::IfCompleted(..)
+        | Command::Peaches
+        | Command::Dig
+    )
+  }
+}
+
+pub struct Story {
+  commands: Vec<Command>,
+  /// The number of `dig` and `carrot reveal` commands.
+  reveals: usize,
+  has_peaches: bool,
+}
+
+impl Story {
+  pub fn load(path: &Path) -> Result<Self, String> {
+    let text = fs::read_to_string(path)
+      .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Self::parse(&text).map_err(|err| format!("{}:{err}", path.display()))
+  }
+
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let lines = text
+      .lines()
+      .enumerate()
+      .map(|(line_idx, line)| (line_idx + 1, line.trim()))
+      .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    // Labels can be jumped to before they are defined, so find them all first.
+    let mut labels = HashMap::new();
+    let mut num_commands = 0;
+    for (line_num, line) in lines.clone() {
+      match line.strip_suffix(':') {
+        Some(label) => {
+          if labels.insert(label, num_commands).is_some() {
+            return Err(format!("{line_num}: Duplicate label: '{line}'"));
+          }
+        }
+        None => num_commands += 1,
+      }
+    }
+
+    let lines: Vec<_> = lines.filter(|(_, line)| !line.ends_with(':')).collect();
+    let commands: Vec<_> = lines
+      .iter()
+      .map(|&(line_num, line)| {
+        parse_command(line, &labels).map_err(|msg| format!("{line_num}: {msg}: '{line}'"))
+      })
+      .collect::<Result<_, _>>()?;
+    let story = Self {
+      reveals: commands.iter().filter(|command| command.reveals()).count(),
+      has_peaches: commands.contains(&Command::Peaches),
+      commands,
+    };
+
+    if let Some(idx) = story.find_busy_loop() {
+      let (line_num, line) = lines[idx];
+      return Err(format!(
+        "{line_num}: Loops back without waiting for anything, so it would run forever: '{line}'"
+      ));
+    }
+    Ok(story)
+  }
+
+  /// The commands run right after the one at `idx` without waiting, which may
+  /// be past the end.
+  fn successors(&self, idx: usize) -> Vec<usize> {
+    match &self.commands[idx] {
+      Command::Goto(target) => vec![*target],
+      Command::IfCompleted(_, target) => vec![*target, idx + 1],
+      command if command.is_instant() => vec![idx + 1],
+      _ => vec![],
+    }
+  }
+
+  /// Finds a jump back to a command that can be reached again without any
+  /// blocking command in between, which would keep the story running the
+  /// same commands forever within a single tick.
+  fn find_busy_loop(&self) -> Option<usize> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Visit {
+      Unseen,
+      Open,
+      Done,
+    }
+    let mut visits = vec![Visit::Unseen; self.commands.len()];
+    for start in 0..self.commands.len() {
+      if visits[start] != Visit::Unseen {
+        continue;
+      }
+      // A depth first search over the commands that don't block, with the
+      // successors of each open command still to visit.
+      visits[start] = Visit::Open;
+      let mut stack = vec![(start, self.successors(start))];
+      while let Some((idx, successors)) = stack.last_mut() {
+        let idx = *idx;
+        match successors.pop() {
+          Some(next) if next >= self.commands.len() => {}
+          Some(next) => match visits[next] {
+            Visit::Open => return Some(idx),
+            Visit::Unseen => {
+              visits[next] = Visit::Open;
+              stack.push((next, self.successors(next)));
+            }
+            Visit::Done => {}
+          },
+          None => {
+            visits[idx] = Visit::Done;
+            stack.pop();
+          }
+        }
+      }
+    }
+    None
+  }
+
+  /// How many times part of the greeting is revealed over the story, with
+  /// `num_peaches` in the basket: once for each `dig` and `carrot reveal`, and
+  /// once for each peach smashed while waiting on `peaches`.
+  pub fn num_reveals(&self, num_peaches: usize) -> usize {
+    self.reveals + if self.has_peaches { num_peaches } else { 0 }
+  }
+
+  pub fn command(&self, idx: usize) -> Option<&Command> {
+    self.commands.get(idx)
+  }
+}
+
+fn parse_command(line: &str, labels: &HashMap<&str, usize>) -> Result<Command, String> {
+  if let Some(rest) = line.strip_prefix("after ") {
+    let (ticks, line) = rest
+      .trim_start()
+      .split_once(char::is_whitespace)
+      .ok_or("Expected a command after the delay")?;
+    let command = parse_command(line.trim_start(), labels)?;
+    if !command.is_instant() {
+      return Err("Only commands that run right away can be delayed".to_string());
+    }
+    return Ok(Command::After(number(ticks)?, Box::new(command)));
+  }
+
+  // Only `say` takes a string, which always comes last.
+  let (words, text) = match line.split_once('"') {
+    Some((words, text)) => {
+      let text = text.strip_suffix('"').ok_or("Unterminated string")?;
+      (words, Some(text.to_string()))
+    }
+    None => (line, None),
+  };
+  let words: Vec<_> = words.split_whitespace().collect();
+  let label = |name: &str| {
+    labels
+      .get(name)
+      .copied()
+      .ok_or_else(|| format!("Unknown label '{name}'"))
+  };
+  let pos = |x: &str, y: &str| Ok::<_, String>((number(x)?, number(y)?));
+
+  Ok(match (words.as_slice(), text) {
+    (["say", dx, flags @ ..], Some(text)) => {
+      let mut to_left = false;
+      let mut bold = false;
+      for flag in flags {
+        match *flag {
+          "left" => to_left = true,
+          "bold" => bold = true,
+          _ => return Err(format!("Unknown flag '{flag}'")),
+        }
+      }
+      Command::Say {
+        dx: number(dx)?,
+        to_left,
+        bold,
+        text,
+      }
+    }
+    (_, Some(_)) => return Err("Only 'say' takes a string".to_string()),
+    (["pose", pose], None) => Command::Pose(match *pose {
+      "sleep" => Pose::Sleep,
+      "wake" => Pose::Wake,
+      "stand" => Pose::Stand,
+      "step" => Pose::Step,
+      "blink" => Pose::Blink,
+      "kazoo" => Pose::Kazoo,
+      "blow" => Pose::Blow,
+      "munch" => Pose::Munch,
+      _ => return Err(format!("Unknown pose '{pose}'")),
+    }),
+    (["face", "left"], None) => Command::Face(Direction::Left),
+    (["face", "right"], None) => Command::Face(Direction::Right),
+    (["hide"], None) => Command::Hide,
+    (["wait", "click"], None) => Command::WaitClick(ClickFilter::Any),
+    (["wait", "click", "outside", target], None) => {
+      Command::WaitClick(ClickFilter::Outside(parse_target(target)?))
+    }
+    (["wait", "click", target], None) => Command::WaitClick(ClickFilter::On(parse_target(target)?)),
+    (["wait", ticks], None) => Command::Wait(number(ticks)?),
+    (["choose", options @ ..], None) if !options.is_empty() => Command::Choose(
+      options
+        .iter()
+        .map(|option| {
+          let (target, name) = option
+            .split_once(':')
+            .ok_or_else(|| format!("Expected <target>:<label>, got '{option}'"))?;
+          Ok((parse_target(target)?, label(name)?))
+        })
+        .collect::<Result<_, String>>()?,
+    ),
+    (["blink", "on"], None) => Command::Blink(true),
+    (["blink", "off"], None) => Command::Blink(false),
+    (["walk", x, y], None) => Command::Walk(None, pos(x, y)?),
+    (["walk", target, dx, dy], None) => Command::Walk(Some(parse_target(target)?), pos(dx, dy)?),
+    (["goto", name], None) => Command::Goto(label(name)?),
+    (["if", "completed", n, "goto", name], None) => Command::IfCompleted(number(n)?, label(name)?),
+    (["complete"], None) => Command::Complete,
+    (["train", "freeze"], None) => Command::Train { frozen: true },
+    (["train", "unfreeze"], None) => Command::Train { frozen: false },
+    (["peaches"], None) => Command::Peaches,
+    (["dig"], None) => Command::Dig,
+    (["kazoo", x, y], None) => Command::Kazoo(pos(x, y)?),
+    (["shreek", x, y], None) => Command::Shreek(pos(x, y)?),
+    (["shockwave", x, y, radius, ticks], None) => Command::Force(Force::Shockwave {
+      center: pos(x, y)?,
+      radius: number(radius)?,
+      ticks: number(ticks)?,
+      easing: Easing::EaseOut,
+    }),
+    ([push @ ("push" | "pull"), x, y, radius, distance, ticks], None) => {
+      let distance: i32 = number(distance)?;
+      Command::Force(Force::Radial {
+        center: pos(x, y)?,
+        radius: number(radius)?,
+        distance: if *push == "push" { distance } else { -distance },
+        ticks: number(ticks)?,
+      })
+    }
+    (["vortex", x, y, radius, degrees, ticks], None) => Command::Force(Force::Vortex {
+      center: pos(x, y)?,
+      radius: number(radius)?,
+      degrees: number(degrees)?,
+      ticks: number(ticks)?,
+    }),
+    (["wind", dx, dy, ticks], None) => Command::Force(Force::Wind {
+      offset: pos(dx, dy)?,
+      ticks: number(ticks)?,
+    }),
+    (["carrot", "appear"], None) => Command::Carrot(CarrotAction::Appear),
+    (["carrot", "flip"], None) => Command::Carrot(CarrotAction::Flip),
+    (["carrot", "behead"], None) => Command::Carrot(CarrotAction::Behead),
+    (["carrot", "reveal"], None) => Command::Carrot(CarrotAction::Reveal),
+    (["carrot", "move", x, y], None) => Command::Carrot(CarrotAction::Move(None, pos(x, y)?)),
+    (["carrot", "move", target, dx, dy], None) => Command::Carrot(CarrotAction::Move(
+      Some(parse_target(target)?),
+      pos(dx, dy)?,
+    )),
+    (["munch", delay, period], None) => match number(period)? {
+      0 => return Err("The munch period must be at least 1".to_string()),
+      period => Command::Munch {
+        delay: number(delay)?,
+        period,
+      },
+    },
+    (["zoom", "out"], None) => Command::ZoomOut,
+    (["zoom", "greeting"], None) => Command::ZoomGreeting,
+    (["zoom", x, y, radius], None) => Command::Zoom(pos(x, y)?, number(radius)?),
+    (["zoom2", x, y, radius], None) => Command::Zoom2(pos(x, y)?, number(radius)?),
+    (["end"], None) => Command::End,
+    _ => return Err("Unknown command".to_string()),
+  })
+}
+
+fn parse_target(name: &str) -> Result<Target, String> {
+  match name {
+    "bunny" => Ok(Target::Bunny),
+    "basket" => Ok(Target::Basket),
+    "hole" => Ok(Target::Hole),
+    "carrot" => Ok(Target::Carrot),
+    _ => Err(format!("Unknown target '{name}'")),
+  }
+}
+
+fn number<N: FromStr>(word: &str) -> Result<N, String> {
+  word
+    .parse()
+    .map_err(|_| format!("Expected a number, got '{word}'"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn loops_must_wait() {
+    let err = Story::parse("top:\n  pose stand\n  goto top\n")
+      .err()
+      .unwrap();
+    assert_eq!(
+      err,
+      "3: Loops back without waiting for anything, so it would run forever: 'goto top'"
+    );
+    let err = Story::parse("top:\n  if completed 1 goto top\n")
+      .err()
+      .unwrap();
+    assert!(err.starts_with("2: Loops back"), "{err}");
+
+    assert!(Story::parse("top:\n  pose stand\n  wait click\n  goto top\n").is_ok());
+    assert!(Story::parse("  goto end\nend:\n  pose stand\n").is_ok());
+  }
+
+  #[test]
+  fn walks_can_be_relative() {
+    let story = Story::parse("walk 22 11\nwalk basket 13 1\nwalk hole -7 0\n").unwrap();
+    assert_eq!(story.command(0), Some(&Command::Walk(None, (22, 11))));
+    assert_eq!(
+      story.command(1),
+      Some(&Command::Walk(Some(Target::Basket), (13, 1)))
+    );
+    assert_eq!(
+      story.command(2),
+      Some(&Command::Walk(Some(Target::Hole), (-7, 0)))
+    );
+    let err = Story::parse("walk train 1 1\n").err().unwrap();
+    assert_eq!(err, "1: Unknown target 'train': 'walk train 1 1'");
+  }
+
+  #[test]
+  fn reveals_are_counted() {
+    assert_eq!(Story::parse(DEFAULT_STORY).unwrap().num_reveals(4), 6);
+    let story = Story::parse("dig\ncarrot reveal\nafter 10 carrot reveal\nend\n").unwrap();
+    assert_eq!(story.num_reveals(4), 3);
+    assert_eq!(Story::parse("peaches\n").unwrap().num_reveals(4), 4);
+    assert_eq!(Story::parse("end\n").unwrap().num_reveals(4), 0);
+  }
+
+  #[test]
+  fn default_story_parses() {
+    assert!(Story::parse(DEFAULT_STORY).is_ok());
+  }
+}
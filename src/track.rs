@@ -1,6 +1,10 @@
-use termion::color;
+use termion::{color, event::Key};
 
-use crate::{entity::Entity, util::Draw};
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  util::Draw,
+};
 
 const Z_IDX: i32 = 20;
 
@@ -13,6 +17,11 @@ impl Track {
   pub fn new(y: u32, width: u32) -> Self {
     Self { y, width }
   }
+
+  /// The top left corner and size of the track.
+  pub fn footprint(&self) -> ((i32, i32), (i32, i32)) {
+    ((0, self.y as i32), (self.width as i32, 1))
+  }
 }
 
 impl Entity for Track {
@@ -30,7 +39,24 @@ impl Entity for Track {
 
   fn tick(&mut self, _t: usize) {}
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, _tuning: &Tuning) {}
 }
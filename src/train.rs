@@ -1,126 +1,150 @@
-use termion::color;
+use termion::event::Key;
 
-use crate::{entity::Entity, util::Draw};
+use crate::{
+  asset::Assets,
+  entity::{Bounds, Entity},
+  sprite::Sprite,
+  tuning::Tuning,
+  tween::{Easing, Tween},
+  util::Draw,
+};
 
 const Z_IDX: i32 = 21;
-
-#[rustfmt::skip]
-const TRAIN_ENGINE: [&str; 3] = [
-  r#"      _---------------------------------------="#,
-  r#"  __/_2 ||  o o o o o o o o o o o o o o o  || |"#,
-  r#"(___---______________________________---______|"#,
-];
-
-#[rustfmt::skip]
-const TRAIN_CABOOSE: [&str; 3] = [
-  r#"---------------------------------------_      "#,
-  r#" ||  o o o o o o o o o o o o o o o  || c_\__  "#,
-  r#"______---______________________________---___)"#,
-];
-
-#[rustfmt::skip]
-const TRAIN_CABIN: [&str; 3] = [
-  r#"---------------------------------------="#,
-  r#" ||  o o o o o o o o o o o o o o o  || |"#,
-  r#"______---_____________________---______|"#,
-];
+/// Ticks it takes to get up to full speed, and to come to a stop.
+const ACCELERATE_TICKS: usize = 100;
+const BRAKE_TICKS: usize = 60;
 
 pub struct Train {
   // Total count of cabin + engine cars (one engine on each end).
   len: u32,
   y: u32,
-  x: i32,
+  x: f32,
   orig_x: i32,
+  t: usize,
+  /// Columns moved per tick at full speed.
+  speed: i32,
+  /// The fraction of full speed the train is going, from when it last started
+  /// speeding up or slowing down.
+  throttle: (usize, Tween<f32>),
+  engine: Sprite,
+  cabin: Sprite,
+  caboose: Sprite,
 }
 
 impl Train {
-  pub fn new(len: u32, x: i32, y: u32) -> Self {
+  pub fn new(len: u32, x: i32, y: u32, assets: &Assets, tuning: &Tuning) -> Self {
+    let car = |name| assets.get(name).sprite().with_z(Z_IDX);
     Self {
       len,
-      x,
+      x: x as f32,
       y,
       orig_x: x,
+      t: 0,
+      speed: tuning.train_speed,
+      throttle: (0, Tween::new(0.)),
+      engine: car("train_engine"),
+      cabin: car("train_cabin"),
+      caboose: car("train_caboose"),
     }
   }
 
   pub fn reset(&mut self) {
-    self.x = self.orig_x;
+    self.x = self.orig_x as f32;
   }
 
-  pub fn collides_with_front(&self, pos: (i32, i32)) -> bool {
-    (0..4).contains(&(pos.0 - self.x)) && (pos.1 - self.y as i32) == 2
+  /// Eases the train up to full speed.
+  pub fn accelerate(&mut self) {
+    self.set_throttle(1., ACCELERATE_TICKS, Easing::EaseIn);
   }
 
-  fn train_len(&self) -> i32 {
-    let engine_len = TRAIN_ENGINE[0].chars().count() as i32;
-    let cabin_len = TRAIN_CABIN[0].chars().count() as i32;
-    let caboose_len = TRAIN_CABOOSE[0].chars().count() as i32;
-    engine_len + cabin_len * (self.len as i32 - 2) + caboose_len
+  /// Eases the train to a stop.
+  pub fn brake(&mut self) {
+    self.set_throttle(0., BRAKE_TICKS, Easing::EaseOut);
   }
 
-  pub fn left_x(&self) -> i32 {
-    self.x
+  fn throttle(&self) -> f32 {
+    let (start, tween) = &self.throttle;
+    tween.value_at(self.t - start)
   }
 
-  pub fn right_x(&self) -> i32 {
-    self.x + self.train_len() - 1
+  fn set_throttle(&mut self, to: f32, ticks: usize, easing: Easing) {
+    self.throttle = (self.t, Tween::new(self.throttle()).then(to, ticks, easing));
+  }
+
+  fn x(&self) -> i32 {
+    self.x.round() as i32
+  }
+
+  /// Moves the train onto a new track height, and changes where it respawns.
+  pub fn relayout(&mut self, orig_x: i32, y: u32) {
+    self.orig_x = orig_x;
+    self.y = y;
+  }
+
+  pub fn collides_with_front(&self, pos: (i32, i32)) -> bool {
+    (0..4).contains(&(pos.0 - self.x())) && (pos.1 - self.y as i32) == 2
+  }
+
+  fn train_len(&self) -> i32 {
+    let engine_len = self.engine.width() as i32;
+    let cabin_len = self.cabin.width() as i32;
+    let caboose_len = self.caboose.width() as i32;
+    engine_len + cabin_len * (self.len as i32 - 2) + caboose_len
   }
 }
 
 impl Entity for Train {
-  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (crate::util::Draw, (i32, i32))> + '_> {
-    let engine_len = TRAIN_ENGINE[0].chars().count() as u32;
-    let cabin_len = TRAIN_CABIN[0].chars().count() as u32;
-    let caboose_len = TRAIN_CABOOSE[0].chars().count() as u32;
+  fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
+    let engine_len = self.engine.width() as i32;
+    let cabin_len = self.cabin.width() as i32;
 
     Box::new((0..self.len).flat_map(move |car_idx| {
-      let car_tiles = if car_idx == 0 {
-        &TRAIN_ENGINE
+      let car = if car_idx == 0 {
+        &self.engine
       } else if car_idx == self.len - 1 {
-        &TRAIN_CABOOSE
+        &self.caboose
       } else {
-        &TRAIN_CABIN
+        &self.cabin
       };
 
       let offset = if car_idx > 0 {
-        engine_len + (car_idx - 1) * cabin_len
+        engine_len + (car_idx as i32 - 1) * cabin_len
       } else {
         0
-      } as i32;
-      let x_offset = self.x + offset;
-
-      car_tiles.iter().enumerate().flat_map(move |(y, row)| {
-        let y = y as i32 + self.y as i32;
-
-        row.chars().enumerate().filter_map(move |(x, c)| {
-          if c == ' '
-            && ((car_idx == 0 && x < 6)
-              || (car_idx == self.len - 1 && x >= caboose_len as usize - 6))
-          {
-            return None;
-          }
-
-          let x = x as i32 + x_offset;
-          Some((
-            Draw::new(c)
-              .with_fg(color::AnsiValue::grayscale(23))
-              .with_z(Z_IDX),
-            (x, y),
-          ))
-        })
-      })
+      };
+      car.draw((self.x() + offset, self.y as i32))
     }))
   }
 
-  fn tick(&mut self, _t: usize) {
-    self.x -= 2;
+  fn tick(&mut self, t: usize) {
+    self.t = t;
+    self.x -= self.speed as f32 * self.throttle();
 
-    if self.train_len() + self.x < -300 {
+    if self.train_len() + self.x() < -300 {
       self.reset();
     }
   }
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, _width: u32, _height: u32) {}
+
+  fn retune(&mut self, tuning: &Tuning) {
+    self.speed = tuning.train_speed;
+  }
 }
@@ -1,42 +1,54 @@
-use crate::{entity::Entity, track::Track, train::Train};
+use termion::event::Key;
 
-enum State {
-  Freezing,
-  Frozen,
-  Moving,
+use crate::{
+  asset::Assets,
+  entity::{dispatch, Bounds, Entity},
+  track::Track,
+  train::Train,
+  tuning::Tuning,
+};
+
+/// The rows the track and the train run along in a window `height` tall. The
+/// train sits on the track, but in a window too short for that it is drawn
+/// from the top.
+fn rows(height: u32) -> (u32, u32) {
+  let track_y = height * 5 / 8;
+  (track_y, track_y.saturating_sub(2))
 }
 
 pub struct TrainScene {
-  state: State,
   track: Track,
   train: Train,
-  width: u32,
 }
 
 impl TrainScene {
-  pub fn new(width: u32, height: u32) -> Self {
+  pub fn new(width: u32, height: u32, assets: &Assets, tuning: &Tuning) -> Self {
+    let (track_y, train_y) = rows(height);
     Self {
-      state: State::Frozen,
-      track: Track::new(height * 5 / 8, width),
-      train: Train::new(5, 4 * width as i32, height * 5 / 8 - 2),
-      width,
+      track: Track::new(track_y, width),
+      train: Train::new(5, 4 * width as i32, train_y, assets, tuning),
     }
   }
 
+  /// Brings the train to a stop wherever it is.
   pub fn freeze(&mut self) {
-    self.state = State::Freezing;
+    self.train.brake();
   }
 
   pub fn unfreeze(&mut self) {
-    self.state = State::Moving;
+    self.train.accelerate();
   }
 
   pub fn train(&self) -> &Train {
     &self.train
   }
 
-  fn train_is_visible(&self) -> bool {
-    self.train.left_x() < self.width as i32 && self.train.right_x() >= 0
+  pub fn track(&self) -> &Track {
+    &self.track
+  }
+
+  fn children_mut(&mut self) -> [&mut dyn Entity; 2] {
+    [&mut self.track, &mut self.train]
   }
 }
 
@@ -46,35 +58,41 @@ impl Entity for TrainScene {
   }
 
   fn tick(&mut self, t: usize) {
-    match self.state {
-      State::Frozen => {}
-      State::Freezing => {
-        if !self.train_is_visible() {
-          self.state = State::Frozen;
-        } else {
-          self.track.tick(t);
-          self.train.tick(t);
-        }
-      }
-      State::Moving => {
-        self.track.tick(t);
-        self.train.tick(t);
-      }
-    }
+    self.track.tick(t);
+    self.train.tick(t);
+  }
+
+  fn bounds(&self) -> Option<Bounds> {
+    Bounds::enclosing([self.track.bounds(), self.train.bounds()])
+  }
+
+  fn click(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.click(x, y)).is_some()
+  }
+
+  fn drag(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.drag(x, y)).is_some()
+  }
+
+  fn release(&mut self, x: u32, y: u32) -> bool {
+    dispatch(self.children_mut(), (x, y), |entity| entity.release(x, y)).is_some()
   }
 
-  fn click(&mut self, x: u32, y: u32) {
-    self.track.click(x, y);
-    self.train.click(x, y);
+  fn key(&mut self, key: Key) -> bool {
+    self
+      .children_mut()
+      .into_iter()
+      .any(|entity| entity.key(key))
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
-    self.track.drag(x, y);
-    self.train.drag(x, y);
+  fn resize(&mut self, width: u32, height: u32) {
+    let (track_y, train_y) = rows(height);
+    self.track = Track::new(track_y, width);
+    self.train.relayout(4 * width as i32, train_y);
   }
 
-  fn release(&mut self, x: u32, y: u32) {
-    self.track.release(x, y);
-    self.train.release(x, y);
+  fn retune(&mut self, tuning: &Tuning) {
+    self.track.retune(tuning);
+    self.train.retune(tuning);
   }
 }
@@ -0,0 +1,221 @@
+//! Tuning files, which set where things are and how they move.
+//!
+//! Each line is `<name> = <value>`. Blank lines and lines starting with `#`
+//! are ignored, and anything not given keeps its default. The names are:
+//!
+//! - `basket`, `hole`, `carrot`: `<x> <y>` of where each starts out in a
+//!   120x40 window. In other windows, they are moved in proportion.
+//! - `step_period`: ticks the bunny takes per step, which covers a column or
+//!   half a row.
+//! - `water_c`: how fast ripples spread across the water.
+//! - `water_damping`: how much of its speed the water keeps each step, from 0
+//!   to 1. With `water_c`, `water_damping + 8.8 * water_c^2` must be at most 1
+//!   for ripples to die down.
+//! - `shreek_ticks`: how long the shockwave of the kazoo lasts.
+//! - `train_speed`: columns the train moves per tick at full speed.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  str::FromStr,
+  time::SystemTime,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Tuning {
+  pub basket: (i32, i32),
+  pub hole: (i32, i32),
+  pub carrot: (i32, i32),
+  pub step_period: usize,
+  pub water_c: f32,
+  pub water_damping: f32,
+  pub shreek_ticks: usize,
+  pub train_speed: i32,
+}
+
+impl Default for Tuning {
+  fn default() -> Self {
+    Self {
+      basket: (9, 10),
+      hole: (102, 12),
+      carrot: (30, 14),
+      step_period: 10,
+      water_c: 0.02,
+      water_damping: 0.996,
+      shreek_ticks: 125,
+      train_speed: 2,
+    }
+  }
+}
+
+impl Tuning {
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let mut tuning = Self::default();
+    // The last line that set the water, which is blamed if it's unstable.
+    let mut water_line = None;
+    for (line_num, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      tuning
+        .set(line)
+        .map_err(|msg| format!("{}: {msg}: '{line}'", line_num + 1))?;
+      if line.starts_with("water_") {
+        water_line = Some((line_num, line));
+      }
+    }
+    if let (Err(msg), Some((line_num, line))) = (tuning.check_water(), water_line) {
+      return Err(format!("{}: {msg}: '{line}'", line_num + 1));
+    }
+    Ok(tuning)
+  }
+
+  /// Whether ripples die down rather than growing without bound, which takes
+  /// `water_damping + 8.8 * water_c^2` of at most 1.
+  fn check_water(&self) -> Result<(), String> {
+    if self.water_damping + 8.8 * self.water_c * self.water_c > 1.0 {
+      return Err("The water is unstable, lower water_c or water_damping".to_string());
+    }
+    Ok(())
+  }
+
+  /// Sets one value from a `<name> = <value>` line.
+  pub fn set(&mut self, line: &str) -> Result<(), String> {
+    let (name, value) = line.split_once('=').ok_or("Expected <name> = <value>")?;
+    let value = value.trim();
+    match name.trim() {
+      "basket" => self.basket = pos(value)?,
+      "hole" => self.hole = pos(value)?,
+      "carrot" => self.carrot = pos(value)?,
+      "step_period" => match number(value)? {
+        0 => return Err("The step period must be at least 1".to_string()),
+        period => self.step_period = period,
+      },
+      "water_c" => match number(value)? {
+        c if c >= 0.0 => self.water_c = c,
+        _ => return Err("The ripple speed must be at least 0".to_string()),
+      },
+      "water_damping" => match number(value)? {
+        damping if (0.0..=1.0).contains(&damping) => self.water_damping = damping,
+        _ => return Err("The water damping must be between 0 and 1".to_string()),
+      },
+      "shreek_ticks" => self.shreek_ticks = number(value)?,
+      "train_speed" => match number(value)? {
+        speed if speed >= 1 => self.train_speed = speed,
+        _ => return Err("The train speed must be at least 1".to_string()),
+      },
+      name => return Err(format!("Unknown name '{name}'")),
+    }
+    Ok(())
+  }
+
+  /// Every value, as `<name> = <value>` lines that `set` reads back exactly.
+  pub fn lines(&self) -> Vec<String> {
+    vec![
+      format!("basket = {} {}", self.basket.0, self.basket.1),
+      format!("hole = {} {}", self.hole.0, self.hole.1),
+      format!("carrot = {} {}", self.carrot.0, self.carrot.1),
+      format!("step_period = {}", self.step_period),
+      format!("water_c = {}", self.water_c),
+      format!("water_damping = {}", self.water_damping),
+      format!("shreek_ticks = {}", self.shreek_ticks),
+      format!("train_speed = {}", self.train_speed),
+    ]
+  }
+}
+
+/// A tuning file that is read again whenever it changes.
+pub struct TuningFile {
+  path: PathBuf,
+  /// When the file had last been modified as of the last poll, or `None` if
+  /// it was missing. Unset until the first poll.
+  modified: Option<Option<SystemTime>>,
+}
+
+impl TuningFile {
+  pub fn new(path: &Path) -> Self {
+    Self {
+      path: path.to_path_buf(),
+      modified: None,
+    }
+  }
+
+  /// Reads the file if it has changed since the last poll, returning the new
+  /// tuning, or why it couldn't be read.
+  pub fn poll(&mut self) -> Option<Result<Tuning, String>> {
+    let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified());
+    let seen = modified.as_ref().ok().copied();
+    if self.modified == Some(seen) {
+      return None;
+    }
+    self.modified = Some(seen);
+
+    let read_err = |err| format!("Failed to read {}: {err}", self.path.display());
+    Some(
+      modified
+        .and_then(|_| fs::read_to_string(&self.path))
+        .map_err(read_err)
+        .and_then(|text| {
+          Tuning::parse(&text).map_err(|err| format!("{}:{err}", self.path.display()))
+        }),
+    )
+  }
+}
+
+fn number<N: FromStr>(word: &str) -> Result<N, String> {
+  word
+    .parse()
+    .map_err(|_| format!("Expected a number, got '{word}'"))
+}
+
+fn pos(value: &str) -> Result<(i32, i32), String> {
+  match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+    [x, y] => Ok((number(x)?, number(y)?)),
+    _ => Err("Expected <x> <y>".to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn lines_round_trip() {
+    let mut tuning = Tuning::default();
+    for line in Tuning::parse("hole = 90 8\nwater_c = 0.01\n")
+      .unwrap()
+      .lines()
+    {
+      tuning.set(&line).unwrap();
+    }
+    assert_eq!(tuning.hole, (90, 8));
+    assert_eq!(tuning.water_c, 0.01);
+  }
+
+  #[test]
+  fn rejects_values_that_break_the_scene() {
+    let err = |line: &str| Tuning::default().set(line).err().unwrap();
+    assert_eq!(err("step_period = 0"), "The step period must be at least 1");
+    assert_eq!(err("train_speed = 0"), "The train speed must be at least 1");
+    assert_eq!(err("water_c = -0.1"), "The ripple speed must be at least 0");
+    for damping in ["-0.5", "1.5", "NaN"] {
+      let line = format!("water_damping = {damping}");
+      assert_eq!(err(&line), "The water damping must be between 0 and 1");
+    }
+    assert!(Tuning::default().set("water_damping = 1").is_ok());
+  }
+
+  #[test]
+  fn rejects_unstable_water_on_the_last_line_that_set_it() {
+    assert_eq!(
+      Tuning::parse("water_damping = 0.9\n\nwater_c = 0.5\n")
+        .err()
+        .unwrap(),
+      "3: The water is unstable, lower water_c or water_damping: 'water_c = 0.5'"
+    );
+    // Lowering the damping afterwards makes room for the faster ripples.
+    let tuning = Tuning::parse("water_c = 0.1\nwater_damping = 0.9\n").unwrap();
+    assert_eq!(tuning.water_damping, 0.9);
+  }
+}
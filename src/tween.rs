@@ -0,0 +1,146 @@
+use std::f32::consts::PI;
+
+use crate::color::Rgb;
+
+/// How a tween moves between its ends, as a function from the fraction of time
+/// passed to the fraction of the way there.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+  Linear,
+  /// Starts slow and speeds up.
+  EaseIn,
+  /// Starts fast and slows down.
+  EaseOut,
+  /// Speeds up, then slows down.
+  EaseInOut,
+  /// Overshoots and bounces back, like a ball dropped onto the end.
+  Bounce,
+  /// Overshoots and springs back and forth around the end.
+  Elastic,
+}
+
+impl Easing {
+  pub fn apply(self, s: f32) -> f32 {
+    let s = s.clamp(0., 1.);
+    match self {
+      Easing::Linear => s,
+      Easing::EaseIn => s * s,
+      Easing::EaseOut => 1. - (1. - s) * (1. - s),
+      Easing::EaseInOut => {
+        if s < 0.5 {
+          2. * s * s
+        } else {
+          1. - 2. * (1. - s) * (1. - s)
+        }
+      }
+      Easing::Bounce => {
+        const N: f32 = 7.5625;
+        const D: f32 = 2.75;
+        if s < 1. / D {
+          N * s * s
+        } else if s < 2. / D {
+          let s = s - 1.5 / D;
+          N * s * s + 0.75
+        } else if s < 2.5 / D {
+          let s = s - 2.25 / D;
+          N * s * s + 0.9375
+        } else {
+          let s = s - 2.625 / D;
+          N * s * s + 0.984375
+        }
+      }
+      Easing::Elastic => {
+        if s == 0. || s == 1. {
+          s
+        } else {
+          2f32.powf(-10. * s) * ((10. * s - 0.75) * 2. * PI / 3.).sin() + 1.
+        }
+      }
+    }
+  }
+}
+
+/// Something that can be tweened.
+pub trait Lerp: Copy {
+  /// The value a fraction `s` of the way from `self` to `to`. `s` may be
+  /// outside of [0, 1] for easings that overshoot.
+  fn lerp(self, to: Self, s: f32) -> Self;
+}
+
+impl Lerp for f32 {
+  fn lerp(self, to: Self, s: f32) -> Self {
+    self + (to - self) * s
+  }
+}
+
+impl Lerp for i32 {
+  fn lerp(self, to: Self, s: f32) -> Self {
+    (self as f32).lerp(to as f32, s).round() as i32
+  }
+}
+
+impl Lerp for u8 {
+  fn lerp(self, to: Self, s: f32) -> Self {
+    (self as f32).lerp(to as f32, s).round().clamp(0., 255.) as u8
+  }
+}
+
+impl Lerp for Rgb {
+  fn lerp(self, to: Self, s: f32) -> Self {
+    Rgb(
+      self.0.lerp(to.0, s),
+      self.1.lerp(to.1, s),
+      self.2.lerp(to.2, s),
+    )
+  }
+}
+
+/// Pairs are tweened in parallel, so positions move in a straight line.
+impl<A: Lerp, B: Lerp> Lerp for (A, B) {
+  fn lerp(self, to: Self, s: f32) -> Self {
+    (self.0.lerp(to.0, s), self.1.lerp(to.1, s))
+  }
+}
+
+/// A value that moves through a chain of segments over time, each starting
+/// where the last one ended.
+#[derive(Clone)]
+pub struct Tween<T> {
+  from: T,
+  /// Where each segment ends, how many ticks it takes, and how it gets there.
+  segments: Vec<(T, usize, Easing)>,
+}
+
+impl<T: Lerp> Tween<T> {
+  /// A tween that stays at `from` until segments are added.
+  pub fn new(from: T) -> Self {
+    Self {
+      from,
+      segments: Vec::new(),
+    }
+  }
+
+  /// Adds a segment that moves to `to` over `ticks`, once the rest are done.
+  pub fn then(mut self, to: T, ticks: usize, easing: Easing) -> Self {
+    self.segments.push((to, ticks, easing));
+    self
+  }
+
+  pub fn duration(&self) -> usize {
+    self.segments.iter().map(|&(_, ticks, _)| ticks).sum()
+  }
+
+  /// The value `dt` ticks in. Once every segment is done, this is where the
+  /// last one ended.
+  pub fn value_at(&self, mut dt: usize) -> T {
+    let mut from = self.from;
+    for &(to, ticks, easing) in &self.segments {
+      if dt < ticks {
+        return from.lerp(to, easing.apply(dt as f32 / ticks as f32));
+      }
+      dt -= ticks;
+      from = to;
+    }
+    from
+  }
+}
@@ -1,37 +1,11 @@
 use std::fmt::Display;
 
-use termion::{color, style};
+use termion::style;
 
-const G: f32 = -0.1;
+use crate::color::Rgb;
 
-pub struct Radiate {
-  pub t: usize,
-  pub pos: (i32, i32),
-}
-
-pub fn move_per_radiate(radiate: &Option<Radiate>, t: usize, pos: (i32, i32)) -> (i32, i32) {
-  match radiate {
-    Some(Radiate {
-      t: radiate_t,
-      pos: r_pos,
-    }) => {
-      let dt = (t - radiate_t).min(75);
-      let dx = (pos.0 - r_pos.0) as f32;
-      let dy = (pos.1 - r_pos.1) as f32 * 11. / 5.;
-      let d = dx.powi(2) + dy.powi(2);
-      if (d as usize) < dt * dt {
-        let scale = dt as f32 / d.sqrt();
-        (
-          r_pos.0 + (dx * scale) as i32,
-          r_pos.1 + (dy * scale * 5. / 11.) as i32,
-        )
-      } else {
-        pos
-      }
-    }
-    None => pos,
-  }
-}
+/// How much faster things fall each tick.
+pub const G: f32 = -0.1;
 
 pub fn explosion_target_dt(target: (i32, i32), origin: (i32, i32)) -> usize {
   let dx = (target.0 - origin.0) as f32;
@@ -63,12 +37,66 @@ pub fn explosion_path(dt: f32, target: (i32, i32), origin: (i32, i32)) -> (i32,
   (x_pos, y_pos)
 }
 
-#[derive(Clone)]
+/// Text attributes a tile can be drawn with.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Attrs {
+  pub bold: bool,
+  pub dim: bool,
+  pub italic: bool,
+  pub underline: bool,
+  pub reverse: bool,
+  pub blink: bool,
+}
+
+impl Attrs {
+  /// The attributes set in `self` but not in `other`.
+  pub fn difference(&self, other: &Attrs) -> Attrs {
+    Attrs {
+      bold: self.bold && !other.bold,
+      dim: self.dim && !other.dim,
+      italic: self.italic && !other.italic,
+      underline: self.underline && !other.underline,
+      reverse: self.reverse && !other.reverse,
+      blink: self.blink && !other.blink,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    *self == Attrs::default()
+  }
+}
+
+impl Display for Attrs {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.bold {
+      write!(f, "{}", style::Bold)?;
+    }
+    if self.dim {
+      write!(f, "{}", style::Faint)?;
+    }
+    if self.italic {
+      write!(f, "{}", style::Italic)?;
+    }
+    if self.underline {
+      write!(f, "{}", style::Underline)?;
+    }
+    if self.reverse {
+      write!(f, "{}", style::Invert)?;
+    }
+    if self.blink {
+      write!(f, "{}", style::Blink)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Clone, PartialEq, Eq)]
 pub struct Draw {
   item: char,
-  fg_color: Option<color::AnsiValue>,
+  fg_color: Option<Rgb>,
+  bg_color: Option<Rgb>,
   z_idx: i32,
-  italic: bool,
+  attrs: Attrs,
 }
 
 impl Draw {
@@ -76,8 +104,9 @@ impl Draw {
     Self {
       item,
       fg_color: None,
+      bg_color: None,
       z_idx: 0,
-      italic: false,
+      attrs: Attrs::default(),
     }
   }
 
@@ -85,13 +114,28 @@ impl Draw {
     self.item
   }
 
-  pub fn with_fg(self, color: color::AnsiValue) -> Self {
+  pub fn with_fg<C: Into<Rgb>>(self, color: C) -> Self {
+    Self {
+      fg_color: Some(color.into()),
+      ..self
+    }
+  }
+
+  pub fn with_bg<C: Into<Rgb>>(self, color: C) -> Self {
     Self {
-      fg_color: Some(color),
+      bg_color: Some(color.into()),
       ..self
     }
   }
 
+  pub fn fg(&self) -> Option<Rgb> {
+    self.fg_color
+  }
+
+  pub fn bg(&self) -> Option<Rgb> {
+    self.bg_color
+  }
+
   pub fn with_z(self, z_idx: i32) -> Self {
     Self { z_idx, ..self }
   }
@@ -102,39 +146,19 @@ impl Draw {
 
   pub fn with_italic(self) -> Self {
     Self {
-      italic: true,
+      attrs: Attrs {
+        italic: true,
+        ..self.attrs
+      },
       ..self
     }
   }
-}
 
-impl Display for Draw {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let fg_str = if let Some(color) = self.fg_color {
-      color.fg_string()
-    } else {
-      color::Reset.fg_str().to_owned()
-    };
-    let italic_str = if self.italic {
-      style::Italic.to_string()
-    } else {
-      "".to_owned()
-    };
-    write!(f, "{}{}{}{}", style::Reset, italic_str, fg_str, self.item)
+  pub fn with_attrs(self, attrs: Attrs) -> Self {
+    Self { attrs, ..self }
   }
-}
 
-impl PartialEq for Draw {
-  fn eq(&self, other: &Self) -> bool {
-    self.item == other.item
-      && self.z_idx == other.z_idx
-      && self.italic == other.italic
-      && match (self.fg_color, other.fg_color) {
-        (Some(color::AnsiValue(c1)), Some(color::AnsiValue(c2))) => c1 == c2,
-        (None, None) => true,
-        _ => false,
-      }
+  pub fn attrs(&self) -> Attrs {
+    self.attrs
   }
 }
-
-impl Eq for Draw {}
@@ -1,10 +1,13 @@
 use std::{iter, rc::Rc};
 
-use termion::color;
+use termion::{color, event::Key};
 
-use crate::{entity::Entity, util::Draw};
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  util::Draw,
+};
 
-const C: f32 = 0.02;
 const SCALE: i32 = 2;
 const Z_IDX: i32 = 1;
 
@@ -20,10 +23,15 @@ impl Particle {
   }
 
   /// Left, Up, Right, Down
-  fn perturb(&self, neighbors: (Particle, Particle, Particle, Particle)) -> Self {
+  fn perturb(
+    &self,
+    neighbors: (Particle, Particle, Particle, Particle),
+    c: f32,
+    damping: f32,
+  ) -> Self {
     let ddx = neighbors.2.pos + neighbors.0.pos - 2. * self.pos;
     let ddy = neighbors.3.pos + neighbors.1.pos - 2. * self.pos;
-    let vel = 0.996 * self.vel + (C * C) * (ddx + ddy) + 0.8 * (C * C) * (0.5 - self.pos);
+    let vel = damping * self.vel + (c * c) * (ddx + ddy) + 0.8 * (c * c) * (0.5 - self.pos);
     let pos = self.pos + self.vel;
     Self { pos, vel }
   }
@@ -103,10 +111,14 @@ pub struct Water {
   height: u32,
   grid: Vec<ParticleType>,
   last_mouse: (i32, i32),
+  /// The speed of waves.
+  c: f32,
+  /// How much of its velocity each particle keeps per step.
+  damping: f32,
 }
 
 impl Water {
-  pub fn new(width: u32, height: u32) -> Self {
+  pub fn new(width: u32, height: u32, tuning: &Tuning) -> Self {
     Self {
       width: SCALE as u32 * width,
       height: SCALE as u32 * height,
@@ -115,9 +127,16 @@ impl Water {
         ((SCALE * SCALE) as u32 * width * height) as usize
       ],
       last_mouse: (-1, -1),
+      c: tuning.water_c,
+      damping: tuning.water_damping,
     }
   }
 
+  /// The size of the water in tiles.
+  pub fn size(&self) -> (u32, u32) {
+    (self.width / SCALE as u32, self.height / SCALE as u32)
+  }
+
   pub fn is_wet(&self, (x, y): (i32, i32)) -> bool {
     (0..SCALE).any(|dy| (0..SCALE).any(|dx| !self.get(SCALE * x + dx, SCALE * y + dy).is_fixed()))
   }
@@ -186,12 +205,16 @@ impl Water {
     self.grid = (0..self.height as i32)
       .flat_map(|y| (0..self.width as i32).map(move |x| (x, y)))
       .map(|(x, y)| match self.get(x, y) {
-        ParticleType::Normal(particle) => ParticleType::Normal(particle.perturb((
-          self.get_particle(x - 1, y),
-          self.get_particle(x, y - 1),
-          self.get_particle(x + 1, y),
-          self.get_particle(x, y + 1),
-        ))),
+        ParticleType::Normal(particle) => ParticleType::Normal(particle.perturb(
+          (
+            self.get_particle(x - 1, y),
+            self.get_particle(x, y - 1),
+            self.get_particle(x + 1, y),
+            self.get_particle(x, y + 1),
+          ),
+          self.c,
+          self.damping,
+        )),
         ParticleType::Fixed => ParticleType::Fixed,
       })
       .collect();
@@ -243,15 +266,44 @@ impl Entity for Water {
     }
   }
 
-  fn click(&mut self, x: u32, y: u32) {
+  /// The water ripples wherever the mouse goes that nothing above it takes.
+  fn bounds(&self) -> Option<Bounds> {
+    Some(Bounds::everywhere(Z_IDX))
+  }
+
+  /// Ripples never consume events, so clicks on the water still reach the
+  /// story.
+  fn click(&mut self, x: u32, y: u32) -> bool {
     self.click_tile(x, y);
+    false
   }
 
-  fn drag(&mut self, x: u32, y: u32) {
+  fn drag(&mut self, x: u32, y: u32) -> bool {
     self.click_tile(x, y);
+    false
   }
 
-  fn release(&mut self, _x: u32, _y: u32) {
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
     self.last_mouse = (-1, -1);
+    false
+  }
+
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  /// Resets the water to rest at the new size. Any fixed tiles have to be
+  /// re-applied by the owner.
+  fn resize(&mut self, width: u32, height: u32) {
+    *self = Self {
+      c: self.c,
+      damping: self.damping,
+      ..Self::new(width, height, &Tuning::default())
+    };
+  }
+
+  fn retune(&mut self, tuning: &Tuning) {
+    self.c = tuning.water_c;
+    self.damping = tuning.water_damping;
   }
 }
@@ -1,14 +1,31 @@
 use std::io::Write;
-use termion::cursor;
+use termion::{color, cursor, style};
 
-use crate::util::Draw;
+use crate::{
+  color::{ColorMode, Rgb},
+  util::{Attrs, Draw},
+};
 
 pub struct Window<W: Write> {
   stdout: W,
   width: u32,
   height: u32,
+  color_mode: ColorMode,
   canvas: Vec<Option<Draw>>,
   prev_canvas: Vec<Option<Draw>>,
+  // Escapes for the frame being rendered, written out in one go.
+  frame: Vec<u8>,
+  // Escapes queued outside of a render (like screen clears), which go out
+  // with the next frame.
+  pending: Vec<u8>,
+}
+
+impl Window<std::io::Sink> {
+  /// A window that isn't attached to a terminal. Frames can still be
+  /// composited and inspected with `cell` and `to_text`.
+  pub fn headless(width: u32, height: u32) -> Self {
+    Self::new(std::io::sink(), width, height)
+  }
 }
 
 impl<W: Write> Window<W> {
@@ -17,13 +34,38 @@ impl<W: Write> Window<W> {
       stdout,
       width,
       height,
+      color_mode: ColorMode::Ansi256,
       canvas: (0..(width * height)).map(|_| None).collect(),
       prev_canvas: (0..(width * height)).map(|_| None).collect(),
+      frame: Vec::new(),
+      pending: Vec::new(),
     };
     s.allocate().expect("Failed to initialize window");
     s
   }
 
+  /// The composited tile at `(x, y)`, if anything was drawn there.
+  pub fn cell(&self, x: u32, y: u32) -> Option<&Draw> {
+    self.get(x, y).as_ref()
+  }
+
+  /// The characters of the composited canvas, one line per row, with
+  /// trailing blanks trimmed.
+  pub fn to_text(&self) -> String {
+    (0..self.height)
+      .map(|y| {
+        let line: String = (0..self.width)
+          .map(|x| self.cell(x, y).map_or(' ', |draw| draw.item()))
+          .collect();
+        line.trim_end().to_owned() + "\n"
+      })
+      .collect()
+  }
+
+  pub fn with_color_mode(self, color_mode: ColorMode) -> Self {
+    Self { color_mode, ..self }
+  }
+
   pub fn width(&self) -> u32 {
     self.width
   }
@@ -32,10 +74,6 @@ impl<W: Write> Window<W> {
     self.height
   }
 
-  fn idx_to_pos(&self, idx: usize) -> (u32, u32) {
-    (idx as u32 % self.width, idx as u32 / self.width)
-  }
-
   fn idx(&self, x: u32, y: u32) -> usize {
     (x + y * self.width) as usize
   }
@@ -50,7 +88,23 @@ impl<W: Write> Window<W> {
   }
 
   fn allocate(&mut self) -> std::io::Result<()> {
-    write!(self.stdout, "{}{}", termion::clear::All, cursor::Goto(1, 1))
+    write!(
+      self.pending,
+      "{}{}{}",
+      style::Reset,
+      termion::clear::All,
+      cursor::Goto(1, 1)
+    )
+  }
+
+  /// Reallocates the canvas for a new terminal size. The screen is cleared, so
+  /// the next render repaints every cell.
+  pub fn resize(&mut self, width: u32, height: u32) -> std::io::Result<()> {
+    self.width = width;
+    self.height = height;
+    self.canvas = (0..(width * height)).map(|_| None).collect();
+    self.prev_canvas = (0..(width * height)).map(|_| None).collect();
+    self.allocate()
   }
 
   pub fn reset(&mut self) {
@@ -62,57 +116,222 @@ impl<W: Write> Window<W> {
     write!(self.stdout, "{}", cursor::Goto(1, 1))
   }
 
+  /// Draws `draw` if it is above what is already at `pos`. A tile without a
+  /// background keeps the background of whatever it covers.
   pub fn draw(&mut self, draw: Draw, pos: (u32, u32)) {
-    *self.get_mut(pos.0, pos.1) = Some(self.get(pos.0, pos.1).clone().map_or(
-      draw.clone(),
-      |cur_el| {
-        if cur_el.z_idx() < draw.z_idx() {
-          draw
+    *self.get_mut(pos.0, pos.1) = Some(match self.get(pos.0, pos.1).clone() {
+      Some(cur_el) => {
+        let (top, bottom) = if cur_el.z_idx() < draw.z_idx() {
+          (draw, cur_el)
         } else {
-          cur_el
+          (cur_el, draw)
+        };
+        match (top.bg(), bottom.bg()) {
+          (None, Some(bg)) => top.with_bg(bg),
+          _ => top,
+        }
+      }
+      None => draw,
+    })
+  }
+
+  /// Writes every cell that changed since the last frame, and returns the
+  /// number of bytes sent to the terminal.
+  ///
+  /// Runs of adjacent changed cells are written without moving the cursor,
+  /// and style escapes are only emitted where the style actually changes.
+  pub fn render(&mut self) -> std::io::Result<usize> {
+    self.frame.clear();
+    self.frame.append(&mut self.pending);
+    let mut cursor_pos = None;
+    let mut pen = None;
+
+    for y in 0..self.height {
+      for x in 0..self.width {
+        let idx = self.idx(x, y);
+        let draw = &self.canvas[idx];
+        if *draw == self.prev_canvas[idx] {
+          continue;
+        }
+
+        if cursor_pos != Some((x, y)) {
+          write!(
+            self.frame,
+            "{}",
+            cursor::Goto((x + 1) as u16, (y + 1) as u16)
+          )?;
         }
-      },
-    ))
-  }
-
-  pub fn render(&mut self) -> std::io::Result<()> {
-    let ((min_x, max_x), (min_y, max_y)) = self
-      .canvas
-      .iter()
-      .zip(self.prev_canvas.iter())
-      .enumerate()
-      .fold(
-        ((u32::MAX, 0), (u32::MAX, 0)),
-        |((min_x, max_x), (min_y, max_y)), (idx, (d1, d2))| {
-          if d1 != d2 {
-            let (x, y) = self.idx_to_pos(idx);
-            ((min_x.min(x), max_x.max(x)), (min_y.min(y), max_y.max(y)))
-          } else {
-            ((min_x, max_x), (min_y, max_y))
-          }
-        },
-      );
+
+        let next_pen = Pen::of(draw.as_ref());
+        next_pen.transition_from(pen.as_ref(), self.color_mode, &mut self.frame)?;
+        pen = Some(next_pen);
+        write!(
+          self.frame,
+          "{}",
+          draw.as_ref().map_or(' ', |draw| draw.item())
+        )?;
+
+        // Writing to the last column leaves the cursor in an unreliable
+        // spot, so force a move before the next write.
+        cursor_pos = (x + 1 < self.width).then_some((x + 1, y));
+      }
+    }
 
     // Don't render if no change.
-    if max_x < min_x {
-      return Ok(());
+    if self.frame.is_empty() {
+      return Ok(0);
     }
 
-    for y in min_y..=max_y {
-      write!(
-        self.stdout,
-        "{}",
-        cursor::Goto((min_x + 1) as u16, (y + 1) as u16)
-      )?;
-      for x in min_x..=max_x {
-        if let Some(draw) = self.get(x, y).clone() {
-          write!(self.stdout, "{}", draw)?;
-        } else {
-          write!(self.stdout, " ")?;
+    write!(self.frame, "{}", style::Reset)?;
+    self.stdout.write_all(&self.frame)?;
+    self.stdout.flush()?;
+    Ok(self.frame.len())
+  }
+
+  /// The bytes written by the most recent `render`.
+  pub fn last_frame(&self) -> &[u8] {
+    &self.frame
+  }
+}
+
+/// The colors and attributes the terminal is currently drawing with.
+#[derive(PartialEq, Eq)]
+struct Pen {
+  fg: Option<Rgb>,
+  bg: Option<Rgb>,
+  attrs: Attrs,
+}
+
+impl Pen {
+  fn of(draw: Option<&Draw>) -> Self {
+    match draw {
+      Some(draw) => Self {
+        fg: draw.fg(),
+        bg: draw.bg(),
+        attrs: draw.attrs(),
+      },
+      None => Self {
+        fg: None,
+        bg: None,
+        attrs: Attrs::default(),
+      },
+    }
+  }
+
+  /// Writes the escapes needed to switch the terminal from `prev` to this pen.
+  /// If `prev` is unknown, or has attributes this pen doesn't, the style is
+  /// reset and rebuilt from scratch.
+  fn transition_from<W: Write>(
+    &self,
+    prev: Option<&Pen>,
+    mode: ColorMode,
+    out: &mut W,
+  ) -> std::io::Result<()> {
+    match prev {
+      Some(prev) if prev == self => Ok(()),
+      Some(prev) if prev.attrs.difference(&self.attrs).is_empty() => {
+        write!(out, "{}", self.attrs.difference(&prev.attrs))?;
+        if self.fg != prev.fg {
+          match self.fg {
+            Some(fg) => write!(out, "{}", fg.fg_string(mode))?,
+            None => write!(out, "{}", color::Reset.fg_str())?,
+          }
+        }
+        if self.bg != prev.bg {
+          match self.bg {
+            Some(bg) => write!(out, "{}", bg.bg_string(mode))?,
+            None => write!(out, "{}", color::Reset.bg_str())?,
+          }
+        }
+        Ok(())
+      }
+      _ => {
+        write!(out, "{}{}", style::Reset, self.attrs)?;
+        if let Some(fg) = self.fg {
+          write!(out, "{}", fg.fg_string(mode))?;
+        }
+        if let Some(bg) = self.bg {
+          write!(out, "{}", bg.bg_string(mode))?;
         }
+        Ok(())
       }
     }
-    write!(self.stdout, "{}", cursor::Goto(0, (self.height + 1) as u16))?;
-    self.stdout.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The escapes of a frame drawing `draws` over a blank `width`x`height`
+  /// window that has already been rendered once.
+  fn render(width: u32, height: u32, draws: &[(Draw, (u32, u32))]) -> String {
+    let mut window = Window::new(Vec::new(), width, height);
+    window.render().unwrap();
+    window.reset();
+    for (draw, pos) in draws {
+      window.draw(draw.clone(), *pos);
+    }
+    window.render().unwrap();
+    String::from_utf8(window.last_frame().to_vec()).unwrap()
+  }
+
+  #[test]
+  fn adjacent_cells_share_a_move_and_style() {
+    let red = Rgb(255, 0, 0);
+    let frame = render(
+      5,
+      1,
+      &[
+        (Draw::new('a').with_fg(red), (1, 0)),
+        (Draw::new('b').with_fg(red), (2, 0)),
+      ],
+    );
+    let expected = format!(
+      "{}{}{}ab{}",
+      cursor::Goto(2, 1),
+      style::Reset,
+      red.fg_string(ColorMode::Ansi256),
+      style::Reset
+    );
+    assert_eq!(frame, expected);
+  }
+
+  #[test]
+  fn dropping_an_attribute_resets_the_style() {
+    let bold = Attrs {
+      bold: true,
+      ..Attrs::default()
+    };
+    let frame = render(
+      5,
+      1,
+      &[
+        (Draw::new('a').with_attrs(bold), (0, 0)),
+        (Draw::new('b'), (1, 0)),
+      ],
+    );
+    let expected = format!(
+      "{}{}{}a{}b{}",
+      cursor::Goto(1, 1),
+      style::Reset,
+      style::Bold,
+      style::Reset,
+      style::Reset
+    );
+    assert_eq!(frame, expected);
+  }
+
+  #[test]
+  fn moves_after_writing_the_last_column() {
+    let frame = render(3, 2, &[(Draw::new('a'), (2, 0)), (Draw::new('b'), (0, 1))]);
+    let expected = format!(
+      "{}{}a{}b{}",
+      cursor::Goto(3, 1),
+      style::Reset,
+      cursor::Goto(1, 2),
+      style::Reset
+    );
+    assert_eq!(frame, expected);
   }
 }
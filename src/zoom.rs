@@ -1,13 +1,26 @@
-use crate::{entity::Entity, util::Draw};
+use termion::event::Key;
+
+use crate::{
+  entity::{Bounds, Entity},
+  tuning::Tuning,
+  tween::{Easing, Tween},
+  util::Draw,
+};
 
 const Z_IDX: i32 = 1000;
+/// The radius a zoom closes in from.
+const INIT_RADIUS: f32 = 150.;
+/// Ticks the second zoom takes to open up.
+const ZOOM2_TICKS: usize = 40;
+/// Ticks the zooms take to disappear.
+const DISAPPEAR_TICKS: usize = 50;
 
 enum State {
   Disabled,
   Zoom {
     t: usize,
     pos: (i32, i32),
-    radius: u32,
+    radius: Tween<f32>,
   },
 }
 
@@ -32,19 +45,23 @@ impl Zoom {
     }
   }
 
+  /// Closes in on `pos`, leaving a circle of `radius` around it.
   pub fn zoom(&mut self, pos: (i32, i32), radius: u32) {
+    let radius = radius as f32;
+    let ticks = (INIT_RADIUS - radius).max(0.) as usize;
     self.state = State::Zoom {
       t: self.t,
       pos,
-      radius,
+      radius: Tween::new(INIT_RADIUS).then(radius, ticks, Easing::Bounce),
     };
   }
 
+  /// Opens up a second circle of `radius` around `pos`.
   pub fn zoom2(&mut self, pos: (i32, i32), radius: u32) {
     self.state2 = State::Zoom {
       t: self.t,
       pos,
-      radius,
+      radius: Tween::new(0.).then(radius as f32, ZOOM2_TICKS, Easing::Elastic),
     };
   }
 
@@ -55,24 +72,21 @@ impl Zoom {
 
 impl Entity for Zoom {
   fn iterate_tiles(&self) -> Box<dyn Iterator<Item = (Draw, (i32, i32))> + '_> {
-    match self.state {
+    match &self.state {
       State::Disabled => Box::new([].into_iter()) as Box<dyn Iterator<Item = (Draw, (i32, i32))>>,
       State::Zoom { t, pos, radius } => {
-        const INIT_RADIUS: i32 = 150;
-        let dt = self.t - t;
-
-        let r = (INIT_RADIUS - dt as i32).max(radius as i32);
+        let pos = *pos;
+        let r = radius.value_at(self.t - t);
 
-        let (pos2, r2) = match self.state2 {
+        let (pos2, r2) = match &self.state2 {
           State::Disabled => ((-1, -1), 0.),
-          State::Zoom { t, pos, radius } => {
-            let dt = self.t - t;
-            (pos, (dt as i32).min(radius as i32) as f32)
-          }
+          State::Zoom { t, pos, radius } => (*pos, radius.value_at(self.t - t)),
         };
 
         let factor = match self.disappear {
-          Some(initial_t) => (1. - (self.t - initial_t) as f32 / 50.).max(0.),
+          Some(initial_t) => Tween::new(1.)
+            .then(0., DISAPPEAR_TICKS, Easing::EaseInOut)
+            .value_at(self.t - initial_t),
           None => 1.,
         };
 
@@ -82,8 +96,7 @@ impl Entity for Zoom {
             let dy = (y - pos.1) as f32 * 11. / 5.;
             let dx2 = (x - pos2.0) as f32;
             let dy2 = (y - pos2.1) as f32 * 11. / 5.;
-            if (dx * dx + dy * dy > (r * r) as f32 * factor
-              && dx2 * dx2 + dy2 * dy2 > r2 * r2 * factor)
+            if (dx * dx + dy * dy > r * r * factor && dx2 * dx2 + dy2 * dy2 > r2 * r2 * factor)
               || factor == 0.
             {
               Some((Draw::new(' ').with_z(Z_IDX), (x, y)))
@@ -100,7 +113,27 @@ impl Entity for Zoom {
     self.t = t;
   }
 
-  fn click(&mut self, _x: u32, _y: u32) {}
-  fn drag(&mut self, _x: u32, _y: u32) {}
-  fn release(&mut self, _x: u32, _y: u32) {}
+  fn bounds(&self) -> Option<Bounds> {
+    None
+  }
+
+  fn click(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn drag(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn release(&mut self, _x: u32, _y: u32) -> bool {
+    false
+  }
+  fn key(&mut self, _key: Key) -> bool {
+    false
+  }
+
+  fn resize(&mut self, width: u32, height: u32) {
+    self.width = width;
+    self.height = height;
+  }
+
+  fn retune(&mut self, _tuning: &Tuning) {}
 }